@@ -0,0 +1,153 @@
+use cityrade_types::world::{TerrainTile, WorldMap};
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Прежнее устройство карты: по записи в хеш-таблице на каждую клетку
+#[derive(Serialize)]
+struct LegacyWorldMap {
+    width: u64,
+    height: u64,
+    terrain: HashMap<(i32, i32), TerrainTile>,
+}
+
+impl LegacyWorldMap {
+    fn new(width: u64, height: u64) -> Self {
+        let mut terrain = HashMap::new();
+        for x in 0..width as i32 {
+            for y in 0..height as i32 {
+                terrain.insert((x, y), TerrainTile::Land);
+            }
+        }
+        LegacyWorldMap {
+            width,
+            height,
+            terrain,
+        }
+    }
+
+    fn set_tile(&mut self, x: i32, y: i32, tile: TerrainTile) {
+        if x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32 {
+            self.terrain.insert((x, y), tile);
+        }
+    }
+
+    fn get_tile(&self, x: i32, y: i32) -> Option<&TerrainTile> {
+        self.terrain.get(&(x, y))
+    }
+}
+
+// Детерминированный рисунок клеток, чтобы обе карты хранили одно и то же
+fn pattern(x: i32, y: i32) -> TerrainTile {
+    match (x * 7 + y * 13) % 5 {
+        0 => TerrainTile::Water,
+        1 => TerrainTile::Forest,
+        2 => TerrainTile::Mountain,
+        3 => TerrainTile::Desert,
+        _ => TerrainTile::Land,
+    }
+}
+
+fn legacy_map(size: u64) -> LegacyWorldMap {
+    let mut world = LegacyWorldMap::new(size, size);
+    for x in 0..size as i32 {
+        for y in 0..size as i32 {
+            world.set_tile(x, y, pattern(x, y));
+        }
+    }
+    world
+}
+
+fn chunked_map(size: u64) -> WorldMap {
+    let mut world = WorldMap::new(size, size);
+    for x in 0..size as i32 {
+        for y in 0..size as i32 {
+            world.set_tile(x, y, pattern(x, y));
+        }
+    }
+    world
+}
+
+fn bench_fill(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fill");
+    group.sample_size(10);
+    for size in [256u64, 1024] {
+        group.bench_with_input(BenchmarkId::new("legacy", size), &size, |b, &size| {
+            b.iter(|| legacy_map(size))
+        });
+        group.bench_with_input(BenchmarkId::new("chunked", size), &size, |b, &size| {
+            b.iter(|| chunked_map(size))
+        });
+    }
+    group.finish();
+}
+
+fn bench_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read");
+    group.sample_size(10);
+    for size in [256u64, 1024] {
+        let legacy = legacy_map(size);
+        let chunked = chunked_map(size);
+        group.bench_with_input(BenchmarkId::new("legacy", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut water = 0;
+                for x in 0..size as i32 {
+                    for y in 0..size as i32 {
+                        if legacy.get_tile(x, y) == Some(&TerrainTile::Water) {
+                            water += 1;
+                        }
+                    }
+                }
+                black_box(water)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("chunked", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut water = 0;
+                for x in 0..size as i32 {
+                    for y in 0..size as i32 {
+                        if chunked.get_tile(x, y) == Some(&TerrainTile::Water) {
+                            water += 1;
+                        }
+                    }
+                }
+                black_box(water)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    group.sample_size(10);
+    let size = 256;
+    let legacy = legacy_map(size);
+    let chunked = chunked_map(size);
+    group.bench_function(BenchmarkId::new("legacy", size), |b| {
+        b.iter(|| ron::to_string(black_box(&legacy)).unwrap().len())
+    });
+    group.bench_function(BenchmarkId::new("chunked", size), |b| {
+        b.iter(|| ron::to_string(black_box(&chunked)).unwrap().len())
+    });
+    group.finish();
+}
+
+// Пустая огромная карта не должна занимать память, пока в нее не пишут
+fn bench_lazy(c: &mut Criterion) {
+    c.bench_function("lazy/new_4096", |b| {
+        b.iter(|| WorldMap::new(black_box(4096), black_box(4096)))
+    });
+    c.bench_function("lazy/sparse_writes_4096", |b| {
+        b.iter(|| {
+            let mut world = WorldMap::new(4096, 4096);
+            for i in 0..1000 {
+                world.set_tile(i * 4 % 4096, i * 7 % 4096, TerrainTile::Mountain);
+            }
+            world.loaded_chunks()
+        })
+    });
+}
+
+criterion_group!(benches, bench_fill, bench_read, bench_serialize, bench_lazy);
+criterion_main!(benches);
@@ -0,0 +1,115 @@
+use crate::building::{Building, BuildingType};
+use crate::resources::ResourceType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+// Здания считаются соседними, если стоят в соседних клетках, включая диагонали
+pub const ADJACENCY_RADIUS: i32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AdjacencyEffect {
+    // Прибавка к производству ресурса зданием (0.2 = +20%)
+    Production { resource: ResourceType, bonus: f32 },
+    // Прибавка к целевому счастью всех жителей города
+    Happiness(f32),
+}
+
+impl fmt::Display for AdjacencyEffect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdjacencyEffect::Production { resource, bonus } => write!(
+                f,
+                "Производство ресурса \"{}\" {:+.0}%",
+                resource,
+                bonus * 100.0
+            ),
+            AdjacencyEffect::Happiness(amount) => write!(f, "Счастье {:+.0}%", amount * 100.0),
+        }
+    }
+}
+
+// Здание `building` получает эффект за каждого соседа типа `neighbor`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdjacencyRule {
+    pub building: BuildingType,
+    pub neighbor: BuildingType,
+    pub effect: AdjacencyEffect,
+}
+
+// Эффект, который получит здание. `building_id` равен None для здания,
+// которое еще только планируется поставить
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdjacencyBonus {
+    pub building_id: Option<String>,
+    pub neighbor_id: Option<String>,
+    pub effect: AdjacencyEffect,
+}
+
+pub fn is_adjacent(a: (i32, i32), b: (i32, i32)) -> bool {
+    a != b && (a.0 - b.0).abs() <= ADJACENCY_RADIUS && (a.1 - b.1).abs() <= ADJACENCY_RADIUS
+}
+
+// Все эффекты соседства между построенными зданиями
+pub fn bonuses(
+    rules: &[AdjacencyRule],
+    buildings: &HashMap<String, Building>,
+) -> Vec<AdjacencyBonus> {
+    let mut result = Vec::new();
+
+    for building in buildings.values() {
+        for neighbor in buildings.values() {
+            if !is_adjacent(building.position, neighbor.position) {
+                continue;
+            }
+            for rule in rules {
+                if rule.building == building.building_type
+                    && rule.neighbor == neighbor.building_type
+                {
+                    result.push(AdjacencyBonus {
+                        building_id: Some(building.id.clone()),
+                        neighbor_id: Some(neighbor.id.clone()),
+                        effect: rule.effect.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    result
+}
+
+// Эффекты, которые появятся, если поставить здание типа `building_type`
+// в клетку `position`: и для нового здания, и для его соседей
+pub fn preview(
+    rules: &[AdjacencyRule],
+    buildings: &HashMap<String, Building>,
+    building_type: &BuildingType,
+    position: (i32, i32),
+) -> Vec<AdjacencyBonus> {
+    let mut result = Vec::new();
+
+    for neighbor in buildings.values() {
+        if !is_adjacent(position, neighbor.position) {
+            continue;
+        }
+        for rule in rules {
+            if &rule.building == building_type && rule.neighbor == neighbor.building_type {
+                result.push(AdjacencyBonus {
+                    building_id: None,
+                    neighbor_id: Some(neighbor.id.clone()),
+                    effect: rule.effect.clone(),
+                });
+            }
+            if rule.building == neighbor.building_type && &rule.neighbor == building_type {
+                result.push(AdjacencyBonus {
+                    building_id: Some(neighbor.id.clone()),
+                    neighbor_id: None,
+                    effect: rule.effect.clone(),
+                });
+            }
+        }
+    }
+
+    result
+}
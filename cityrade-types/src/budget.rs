@@ -0,0 +1,163 @@
+use crate::building::BuildingType;
+use crate::population::{Population, PopulationClass};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+// Максимальная ставка налога для любого класса
+pub const MAX_TAX_RATE: f32 = 0.5;
+// Снижение целевого счастья класса при ставке 100%
+pub const TAX_HAPPINESS_PENALTY: f32 = 0.6;
+
+// Сколько золота за тик приносит один житель класса при ставке 100%
+fn taxable_wealth(class: &PopulationClass) -> f32 {
+    match class {
+        PopulationClass::Peasant => 1.0,
+        PopulationClass::Worker => 1.5,
+        PopulationClass::Merchant => 4.0,
+        PopulationClass::Soldier => 0.5,
+        PopulationClass::Scholar => 1.0,
+        PopulationClass::Noble => 8.0,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxPolicy {
+    pub rates: HashMap<PopulationClass, f32>, // от 0.0 до MAX_TAX_RATE
+}
+
+impl Default for TaxPolicy {
+    fn default() -> Self {
+        let mut rates = HashMap::new();
+        rates.insert(PopulationClass::Peasant, 0.1);
+        rates.insert(PopulationClass::Worker, 0.1);
+        rates.insert(PopulationClass::Merchant, 0.15);
+        rates.insert(PopulationClass::Soldier, 0.0);
+        rates.insert(PopulationClass::Scholar, 0.05);
+        rates.insert(PopulationClass::Noble, 0.2);
+
+        TaxPolicy { rates }
+    }
+}
+
+impl TaxPolicy {
+    pub fn rate(&self, class: &PopulationClass) -> f32 {
+        *self.rates.get(class).unwrap_or(&0.0)
+    }
+
+    pub fn set_rate(&mut self, class: PopulationClass, rate: f32) -> Result<(), String> {
+        if !rate.is_finite() || !(0.0..=MAX_TAX_RATE).contains(&rate) {
+            return Err(format!(
+                "Ставка налога должна быть от 0 до {:.0}%",
+                MAX_TAX_RATE * 100.0
+            ));
+        }
+
+        self.rates.insert(class, rate);
+        Ok(())
+    }
+
+    // Налоговый доход каждого класса за тик
+    pub fn collect(&self, population: &Population) -> Vec<(PopulationClass, i32)> {
+        let mut taxes: Vec<(PopulationClass, i32)> = population
+            .classes
+            .iter()
+            .map(|(class, count)| {
+                let amount = *count as f32 * taxable_wealth(class) * self.rate(class);
+                (class.clone(), amount as i32)
+            })
+            .filter(|(_, amount)| *amount > 0)
+            .collect();
+        taxes.sort_by_key(|(class, _)| format!("{:?}", class));
+        taxes
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BudgetItem {
+    BaseIncome,
+    Building(BuildingType),
+    Modifiers, // Местность, технологии и классы населения
+    Taxes(PopulationClass),
+    Upkeep(BuildingType),
+    Maintenance(BuildingType),
+}
+
+impl fmt::Display for BudgetItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BudgetItem::BaseIncome => write!(f, "Базовый доход"),
+            BudgetItem::Building(building_type) => write!(f, "{}", building_type.display_name()),
+            BudgetItem::Modifiers => write!(f, "Модификаторы"),
+            BudgetItem::Taxes(class) => write!(f, "Налоги ({:?})", class),
+            BudgetItem::Upkeep(building_type) => {
+                write!(f, "Содержание: {}", building_type.display_name())
+            }
+            BudgetItem::Maintenance(building_type) => {
+                write!(f, "Обслуживание: {}", building_type.display_name())
+            }
+        }
+    }
+}
+
+// Доходы и расходы золота за последний тик
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetReport {
+    pub income: Vec<(BudgetItem, i32)>,
+    pub expenses: Vec<(BudgetItem, i32)>, // суммы положительные
+}
+
+impl BudgetReport {
+    pub fn add_income(&mut self, item: BudgetItem, amount: i32) {
+        if amount == 0 {
+            return;
+        }
+        match self
+            .income
+            .iter_mut()
+            .find(|(existing, _)| *existing == item)
+        {
+            Some((_, total)) => *total += amount,
+            None => self.income.push((item, amount)),
+        }
+    }
+
+    pub fn add_expense(&mut self, item: BudgetItem, amount: i32) {
+        if amount == 0 {
+            return;
+        }
+        match self
+            .expenses
+            .iter_mut()
+            .find(|(existing, _)| *existing == item)
+        {
+            Some((_, total)) => *total += amount,
+            None => self.expenses.push((item, amount)),
+        }
+    }
+
+    pub fn total_income(&self) -> i32 {
+        self.income.iter().map(|(_, amount)| amount).sum()
+    }
+
+    pub fn total_expenses(&self) -> i32 {
+        self.expenses.iter().map(|(_, amount)| amount).sum()
+    }
+
+    pub fn balance(&self) -> i32 {
+        self.total_income() - self.total_expenses()
+    }
+
+    pub fn report(&self) -> String {
+        let mut report = format!("Доходы: {}\n", self.total_income());
+        for (item, amount) in &self.income {
+            report.push_str(&format!("  {}: {:+}\n", item, amount));
+        }
+        report.push_str(&format!("Расходы: {}\n", self.total_expenses()));
+        for (item, amount) in &self.expenses {
+            report.push_str(&format!("  {}: -{}\n", item, amount));
+        }
+        report.push_str(&format!("Баланс: {:+}\n", self.balance()));
+        report
+    }
+}
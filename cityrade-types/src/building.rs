@@ -1,155 +1,186 @@
-use crate::resources::{ResourceType, Resources};
+use crate::content;
+use crate::item::GoodType;
+use crate::population::PopulationClass;
+use crate::resources::ResourceType;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Ниже этого состояния здание начинает терять выработку
+pub const POOR_CONDITION: f32 = 0.5;
+// Выработка полностью разрушенного здания
+pub const MIN_CONDITION_EFFICIENCY: f32 = 0.25;
+// Доля стоимости уровня, которую стоит полный ремонт
+pub const REPAIR_COST_FRACTION: f32 = 0.3;
+// С этого уровня улучшения требуют товаров из мастерских
+pub const GOODS_UPGRADE_LEVEL: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BuildingType {
-    Residential, // Увеличивает лимит населения
-    Farm,        // Производит еду
-    LumberMill,  // Производит дерево
-    Mine,        // Производит камень и железо
-    Market,      // Увеличивает доход золота
-    Barracks,    // Тренирует войска
-    PowerPlant,  // Производит энергию
-    Laboratory,  // Исследования
-    Temple,      // Повышает счастье населения
-    WaterMill,   // Увеличивает производство ресурсов
-    Wall,        // Защита города
-    Workshop,    // Улучшает производство предметов
-    CrystalMine, // Производит кристаллы
+    Residential,    // Увеличивает лимит населения
+    Farm,           // Производит еду
+    LumberMill,     // Производит дерево
+    Mine,           // Производит камень и железо
+    Market,         // Увеличивает доход золота
+    Barracks,       // Тренирует войска
+    PowerPlant,     // Производит энергию
+    Laboratory,     // Исследования
+    Temple,         // Повышает счастье населения
+    WaterMill,      // Увеличивает производство ресурсов
+    Wall,           // Защита города
+    Workshop,       // Улучшает производство предметов
+    CrystalMine,    // Производит кристаллы
+    Warehouse,      // Хранит материалы
+    Granary,        // Хранит еду
+    Custom(String), // Здание из пакета контента
 }
 
 impl BuildingType {
-    pub fn display_name(&self) -> &str {
-        match self {
-            BuildingType::Residential => "Жилой дом",
-            BuildingType::Farm => "Ферма",
-            BuildingType::LumberMill => "Лесопилка",
-            BuildingType::Mine => "Шахта",
-            BuildingType::Market => "Рынок",
-            BuildingType::Barracks => "Казармы",
-            BuildingType::PowerPlant => "Электростанция",
-            BuildingType::Laboratory => "Лаборатория",
-            BuildingType::Temple => "Храм",
-            BuildingType::WaterMill => "Водяная мельница",
-            BuildingType::Wall => "Стена",
-            BuildingType::Workshop => "Мастерская",
-            BuildingType::CrystalMine => "Кристальная шахта",
-        }
+    // Встроенные типы зданий, для которых контент обязан дать определение
+    pub fn builtin() -> [BuildingType; 15] {
+        [
+            BuildingType::Residential,
+            BuildingType::Farm,
+            BuildingType::LumberMill,
+            BuildingType::Mine,
+            BuildingType::Market,
+            BuildingType::Barracks,
+            BuildingType::PowerPlant,
+            BuildingType::Laboratory,
+            BuildingType::Temple,
+            BuildingType::WaterMill,
+            BuildingType::Wall,
+            BuildingType::Workshop,
+            BuildingType::CrystalMine,
+            BuildingType::Warehouse,
+            BuildingType::Granary,
+        ]
     }
 
-    pub fn description(&self) -> &str {
-        match self {
-            BuildingType::Residential => "Увеличивает максимальное население города",
-            BuildingType::Farm => "Производит еду для населения",
-            BuildingType::LumberMill => "Добывает дерево из окрестных лесов",
-            BuildingType::Mine => "Добывает камень и железо из недр земли",
-            BuildingType::Market => "Увеличивает доход золота в городе",
-            BuildingType::Barracks => "Позволяет тренировать военные отряды",
-            BuildingType::PowerPlant => "Вырабатывает энергию для города",
-            BuildingType::Laboratory => "Открывает новые технологии",
-            BuildingType::Temple => "Повышает счастье и мораль населения",
-            BuildingType::WaterMill => "Увеличивает общую продуктивность",
-            BuildingType::Wall => "Защищает город от нападений",
-            BuildingType::Workshop => "Улучшает производство и ремесло",
-            BuildingType::CrystalMine => "Добывает редкие магические кристаллы",
+    pub fn display_name(&self) -> String {
+        match content::current().building(self) {
+            Some(definition) => definition.display_name.clone(),
+            None => format!("{:?}", self),
         }
     }
 
+    pub fn description(&self) -> String {
+        content::current()
+            .building(self)
+            .map(|definition| definition.description.clone())
+            .unwrap_or_default()
+    }
+
     pub fn base_cost(&self) -> Vec<(ResourceType, u32)> {
-        match self {
-            BuildingType::Residential => vec![(ResourceType::Wood, 50), (ResourceType::Stone, 30)],
-            BuildingType::Farm => vec![(ResourceType::Wood, 30), (ResourceType::Gold, 20)],
-            BuildingType::LumberMill => vec![
-                (ResourceType::Wood, 20),
-                (ResourceType::Stone, 50),
-                (ResourceType::Gold, 30),
-            ],
-            BuildingType::Mine => vec![
-                (ResourceType::Wood, 40),
-                (ResourceType::Stone, 20),
-                (ResourceType::Gold, 50),
-            ],
-            BuildingType::Market => vec![
-                (ResourceType::Wood, 60),
-                (ResourceType::Stone, 40),
-                (ResourceType::Gold, 100),
-            ],
-            BuildingType::Barracks => vec![
-                (ResourceType::Wood, 80),
-                (ResourceType::Stone, 100),
-                (ResourceType::Iron, 50),
-            ],
-            BuildingType::PowerPlant => vec![
-                (ResourceType::Stone, 150),
-                (ResourceType::Iron, 80),
-                (ResourceType::Gold, 200),
-            ],
-            BuildingType::Laboratory => vec![
-                (ResourceType::Stone, 100),
-                (ResourceType::Crystal, 30),
-                (ResourceType::Gold, 250),
-            ],
-            BuildingType::Temple => vec![
-                (ResourceType::Stone, 200),
-                (ResourceType::Wood, 100),
-                (ResourceType::Gold, 150),
-                (ResourceType::Crystal, 20),
-            ],
-            BuildingType::WaterMill => vec![
-                (ResourceType::Wood, 120),
-                (ResourceType::Stone, 80),
-                (ResourceType::Gold, 100),
-            ],
-            BuildingType::Wall => vec![(ResourceType::Stone, 300), (ResourceType::Iron, 100)],
-            BuildingType::Workshop => vec![
-                (ResourceType::Wood, 150),
-                (ResourceType::Stone, 100),
-                (ResourceType::Iron, 50),
-                (ResourceType::Gold, 120),
-            ],
-            BuildingType::CrystalMine => vec![
-                (ResourceType::Stone, 200),
-                (ResourceType::Iron, 150),
-                (ResourceType::Gold, 300),
-            ],
-        }
+        content::current()
+            .building(self)
+            .map(|definition| definition.base_cost.clone())
+            .unwrap_or_default()
+    }
+
+    // Время строительства уровня в тиках растет линейно с уровнем
+    pub fn build_time(&self, level: u32) -> u32 {
+        content::current()
+            .building(self)
+            .map(|definition| definition.build_time)
+            .unwrap_or(1)
+            * level.max(1)
+    }
+
+    // Снос занимает половину времени строительства текущего уровня
+    pub fn demolition_time(&self, level: u32) -> u32 {
+        (self.build_time(level) / 2).max(1)
+    }
+
+    pub fn maintenance(&self, level: u32) -> Vec<(ResourceType, u32)> {
+        content::current()
+            .building(self)
+            .map(|definition| {
+                definition
+                    .maintenance
+                    .iter()
+                    .map(|(resource, amount)| (resource.clone(), amount.saturating_mul(level)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn storage(&self, level: u32) -> Vec<(ResourceType, u32)> {
+        content::current()
+            .building(self)
+            .map(|definition| {
+                definition
+                    .storage
+                    .iter()
+                    .map(|(resource, amount)| (resource.clone(), amount.saturating_mul(level)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn decay_rate(&self) -> f32 {
+        content::current()
+            .building(self)
+            .map(|definition| definition.decay_rate)
+            .unwrap_or(0.0)
+    }
+
+    pub fn salvage_fraction(&self) -> f32 {
+        content::current()
+            .building(self)
+            .map(|definition| definition.salvage_fraction)
+            .unwrap_or(0.0)
+    }
+
+    pub fn deposit_bonus(&self) -> f32 {
+        content::current()
+            .building(self)
+            .map(|definition| definition.deposit_bonus)
+            .unwrap_or(0.0)
     }
 
     pub fn production_effect(&self, level: u32) -> Vec<(ResourceType, i32)> {
-        let base_effect = match self {
-            BuildingType::Residential => vec![(ResourceType::Population, 10 + level as i32 * 5)],
-            BuildingType::Farm => vec![(ResourceType::Food, 10 + level as i32 * 3)],
-            BuildingType::LumberMill => vec![(ResourceType::Wood, 8 + level as i32 * 2)],
-            BuildingType::Mine => vec![
-                (ResourceType::Stone, 5 + level as i32),
-                (ResourceType::Iron, 2 + (level as i32) / 2),
-            ],
-            BuildingType::Market => vec![(ResourceType::Gold, 15 + level as i32 * 5)],
-            BuildingType::Barracks => vec![
-                (ResourceType::Gold, -(10 + level as i32 * 2)),
-                (ResourceType::Food, -(5 + level as i32)),
-            ],
-            BuildingType::PowerPlant => vec![(ResourceType::Energy, 20 + level as i32 * 10)],
-            BuildingType::Laboratory => vec![
-                (ResourceType::Gold, -(20 + level as i32 * 5)),
-                (ResourceType::Energy, -(5 + level as i32 * 2)),
-            ],
-            BuildingType::Temple => vec![(ResourceType::Gold, -(10 + level as i32 * 3))],
-            BuildingType::WaterMill => vec![
-                (ResourceType::Food, 5 + level as i32),
-                (ResourceType::Wood, 5 + level as i32),
-            ],
-            BuildingType::Wall => vec![],
-            BuildingType::Workshop => vec![
-                (ResourceType::Gold, 10 + level as i32 * 3),
-                (ResourceType::Energy, -(3 + level as i32)),
-            ],
-            BuildingType::CrystalMine => vec![
-                (ResourceType::Crystal, 1 + level as i32 / 3),
-                (ResourceType::Energy, -(10 + level as i32 * 2)),
-            ],
+        content::current()
+            .building(self)
+            .map(|definition| {
+                definition
+                    .production
+                    .iter()
+                    .map(|formula| (formula.resource.clone(), formula.amount(level)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for BuildingType {
+    type Err = String;
+
+    // Разбирает тип здания без учета регистра и разделителей. Неизвестные
+    // названия ищутся среди зданий из пакетов контента
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalize = |name: &str| -> String {
+            name.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect()
         };
-        base_effect
+        let normalized = normalize(s);
+
+        if let Some(building_type) = BuildingType::builtin()
+            .into_iter()
+            .find(|building_type| format!("{:?}", building_type).to_lowercase() == normalized)
+        {
+            return Ok(building_type);
+        }
+
+        content::current()
+            .buildings
+            .keys()
+            .find(|building_type| {
+                matches!(building_type, BuildingType::Custom(name) if normalize(name) == normalized)
+            })
+            .cloned()
+            .ok_or_else(|| format!("Неизвестный тип здания: {}", s))
     }
 }
 
@@ -160,6 +191,14 @@ pub struct Building {
     pub building_type: BuildingType,
     pub level: u32,
     pub position: (i32, i32), // Координаты на карте города
+    #[serde(default)]
+    pub invested: Vec<(ResourceType, u32)>, // Все оплаченные строительство и улучшения
+    #[serde(default = "full_condition")]
+    pub condition: f32, // Состояние здания от 0.0 (руины) до 1.0 (как новое)
+}
+
+fn full_condition() -> f32 {
+    1.0
 }
 
 impl Building {
@@ -175,19 +214,41 @@ impl Building {
             building_type,
             level: 1,
             position,
+            invested: Vec::new(),
+            condition: 1.0,
         }
     }
 
+    pub fn add_investment(&mut self, costs: &[(ResourceType, u32)]) {
+        for (resource, amount) in costs {
+            match self.invested.iter_mut().find(|(r, _)| r == resource) {
+                Some((_, total)) => *total += amount,
+                None => self.invested.push((resource.clone(), *amount)),
+            }
+        }
+    }
+
+    // Ресурсы, которые вернутся при сносе здания
+    pub fn salvage(&self) -> Vec<(ResourceType, u32)> {
+        let fraction = self.building_type.salvage_fraction();
+        self.invested
+            .iter()
+            .map(|(resource, amount)| (resource.clone(), (*amount as f32 * fraction) as u32))
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
+    }
+
     pub fn upgrade(&mut self) {
         self.level += 1;
     }
 
     pub fn get_info(&self) -> String {
         format!(
-            "{} ({}), Уровень: {}\nТип: {}\nОписание: {}",
+            "{} ({}), Уровень: {}, Состояние: {:.0}%\nТип: {}\nОписание: {}",
             self.name,
             self.id,
             self.level,
+            self.condition * 100.0,
             self.building_type.display_name(),
             self.building_type.description()
         )
@@ -207,13 +268,76 @@ impl Building {
         self.building_type.production_effect(self.level)
     }
 
-    pub fn apply_production_to_resources(&self, resources: &mut Resources) {
-        for (resource, amount) in self.production_effect() {
-            if amount > 0 {
-                resources.add(&resource, amount as u32);
-            } else if amount < 0 {
-                let _ = resources.subtract(&resource, (-amount) as u32);
-            }
+    pub fn maintenance(&self) -> Vec<(ResourceType, u32)> {
+        self.building_type.maintenance(self.level)
+    }
+
+    // Товары для улучшения до следующего уровня. Потребность растет с уровнем
+    pub fn upgrade_goods(&self) -> Vec<(GoodType, u32)> {
+        let target_level = self.level + 1;
+        if target_level < GOODS_UPGRADE_LEVEL {
+            return Vec::new();
         }
+
+        let multiplier = target_level - GOODS_UPGRADE_LEVEL + 1;
+        content::current()
+            .building(&self.building_type)
+            .map(|definition| {
+                definition
+                    .upgrade_goods
+                    .iter()
+                    .map(|(good, amount)| (good.clone(), amount.saturating_mul(multiplier)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Здание стареет за тик. Без содержания износ идет вдвое быстрее
+    pub fn decay(&mut self, maintained: bool) {
+        let rate = self.building_type.decay_rate();
+        let rate = if maintained { rate } else { rate * 2.0 };
+        self.condition = (self.condition - rate).max(0.0);
+    }
+
+    // Множитель выработки от состояния здания
+    pub fn condition_efficiency(&self) -> f32 {
+        if self.condition >= POOR_CONDITION {
+            1.0
+        } else {
+            MIN_CONDITION_EFFICIENCY
+                + (1.0 - MIN_CONDITION_EFFICIENCY) * self.condition / POOR_CONDITION
+        }
+    }
+
+    // Стоимость ремонта до полного состояния
+    pub fn repair_cost(&self) -> Vec<(ResourceType, u32)> {
+        let damage = 1.0 - self.condition;
+        self.building_type
+            .base_cost()
+            .into_iter()
+            .map(|(resource, amount)| {
+                let cost = amount as f32 * self.level as f32 * damage * REPAIR_COST_FRACTION;
+                (resource, cost.ceil() as u32)
+            })
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
+    }
+
+    pub fn repair(&mut self) {
+        self.condition = 1.0;
+    }
+
+    // Рабочие места здания с учетом уровня
+    pub fn worker_slots(&self) -> Vec<(PopulationClass, u32)> {
+        content::current()
+            .building(&self.building_type)
+            .map(|definition| {
+                definition
+                    .workers
+                    .iter()
+                    .map(|slot| (slot.class.clone(), slot.per_level * self.level))
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 }
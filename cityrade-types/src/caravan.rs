@@ -0,0 +1,195 @@
+use crate::building::BuildingType;
+use crate::city::City;
+use crate::market::TradeRoute;
+use crate::resources::ResourceType;
+use crate::world::{TerrainTile, WorldMap};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+// Радиус (в клетках), в котором враждебные казармы и стены угрожают каравану
+const HOSTILE_RADIUS: i32 = 5;
+// Максимальный шанс потери каравана за один шаг
+const MAX_STEP_RISK: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CaravanStatus {
+    InTransit,
+    Arrived,
+    Lost,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Caravan {
+    pub id: String,
+    pub route_id: String,
+    pub owner_id: String,
+    pub source_city: String,
+    pub target_city: String,
+    pub resource: ResourceType,
+    pub quantity: u32,
+    pub payment: u32, // Золото покупателя, удерживаемое до прибытия
+    pub escort: u32,  // Количество сопровождающих солдат
+    pub path: Vec<(i32, i32)>,
+    pub progress: usize, // Индекс текущей клетки в пути
+    pub status: CaravanStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CaravanEvent {
+    Arrived {
+        caravan_id: String,
+        route_id: String,
+    },
+    Lost {
+        caravan_id: String,
+        route_id: String,
+        position: (i32, i32),
+    },
+}
+
+impl fmt::Display for CaravanEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaravanEvent::Arrived { route_id, .. } => {
+                write!(f, "Караван по маршруту {} прибыл", route_id)
+            }
+            CaravanEvent::Lost {
+                route_id, position, ..
+            } => write!(
+                f,
+                "Караван по маршруту {} потерян в точке ({}, {})",
+                route_id, position.0, position.1
+            ),
+        }
+    }
+}
+
+impl Caravan {
+    pub fn new(route: &TradeRoute, source: &City, target: &City) -> Caravan {
+        Caravan {
+            id: Uuid::new_v4().to_string(),
+            route_id: route.id.clone(),
+            owner_id: source.owner_id.clone(),
+            source_city: source.name.clone(),
+            target_city: target.name.clone(),
+            resource: route.resource.clone(),
+            quantity: route.quantity,
+            payment: route.total_price(),
+            escort: route.escort,
+            path: plan_path(source.position, target.position),
+            progress: 0,
+            status: CaravanStatus::InTransit,
+        }
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        self.path[self.progress.min(self.path.len() - 1)]
+    }
+
+    pub fn remaining_steps(&self) -> usize {
+        self.path.len() - 1 - self.progress.min(self.path.len() - 1)
+    }
+
+    // Шанс потери каравана на текущей клетке
+    pub fn step_risk(&self, world: &WorldMap, cities: &HashMap<String, City>) -> f32 {
+        let position = self.position();
+
+        let terrain_risk = match world.get_tile(position.0, position.1) {
+            Some(tile) => terrain_risk(tile),
+            None => terrain_risk(&TerrainTile::Unknown),
+        };
+
+        let hostile_risk: f32 = cities
+            .values()
+            .filter(|city| city.owner_id != self.owner_id)
+            .filter(|city| {
+                (city.position.0 - position.0).abs() <= HOSTILE_RADIUS
+                    && (city.position.1 - position.1).abs() <= HOSTILE_RADIUS
+            })
+            .flat_map(|city| city.buildings.values())
+            .map(|building| match building.building_type {
+                BuildingType::Barracks => 0.02 * building.level as f32,
+                BuildingType::Wall => 0.01 * building.level as f32,
+                _ => 0.0,
+            })
+            .sum();
+
+        // Каждый солдат сопровождения снижает риск
+        let escort_factor = 1.0 / (1.0 + self.escort as f32 * 0.1);
+
+        ((terrain_risk + hostile_risk) * escort_factor).clamp(0.0, MAX_STEP_RISK)
+    }
+
+    // Продвигает караван на одну клетку и бросает кубик на потерю
+    pub fn advance(
+        &mut self,
+        world: &WorldMap,
+        cities: &HashMap<String, City>,
+        rng: &mut impl Rng,
+    ) -> &CaravanStatus {
+        if self.status != CaravanStatus::InTransit {
+            return &self.status;
+        }
+
+        if self.remaining_steps() == 0 {
+            self.status = CaravanStatus::Arrived;
+            return &self.status;
+        }
+
+        self.progress += 1;
+
+        if rng.random::<f32>() < self.step_risk(world, cities) {
+            self.status = CaravanStatus::Lost;
+        } else if self.remaining_steps() == 0 {
+            self.status = CaravanStatus::Arrived;
+        }
+
+        &self.status
+    }
+}
+
+fn terrain_risk(tile: &TerrainTile) -> f32 {
+    match tile {
+        TerrainTile::Land | TerrainTile::Coast => 0.002,
+        TerrainTile::Forest | TerrainTile::River => 0.01,
+        TerrainTile::Desert | TerrainTile::Swamp => 0.015,
+        TerrainTile::Mountain => 0.02,
+        TerrainTile::Snow => 0.025,
+        TerrainTile::Water => 0.03,
+        TerrainTile::Building(_) | TerrainTile::City(_) => 0.0,
+        TerrainTile::Unknown => 0.02,
+    }
+}
+
+// Прямой путь между двумя точками (алгоритм Брезенхэма)
+pub fn plan_path(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let mut path = Vec::new();
+
+    let (mut x, mut y) = from;
+    let dx = (to.0 - from.0).abs();
+    let dy = -(to.1 - from.1).abs();
+    let sx = if from.0 < to.0 { 1 } else { -1 };
+    let sy = if from.1 < to.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        path.push((x, y));
+        if (x, y) == to {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+
+    path
+}
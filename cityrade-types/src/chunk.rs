@@ -0,0 +1,90 @@
+use crate::world::TerrainTile;
+use serde::{Deserialize, Serialize};
+
+// Сторона квадратного чанка карты в клетках
+pub const CHUNK_SIZE: i32 = 32;
+
+// Клетки без данных, которые хранятся в чанке одним байтом. Порядок задает
+// идентификаторы в сохранениях, поэтому новые клетки добавляются только в конец
+static BASE_TILES: [TerrainTile; 10] = [
+    TerrainTile::Land,
+    TerrainTile::Water,
+    TerrainTile::Mountain,
+    TerrainTile::Forest,
+    TerrainTile::Desert,
+    TerrainTile::Swamp,
+    TerrainTile::Snow,
+    TerrainTile::River,
+    TerrainTile::Coast,
+    TerrainTile::Unknown,
+];
+const UNKNOWN_ID: u8 = 9;
+
+// Идентификатор клетки в чанке. Здания и города в чанках не хранятся
+pub fn tile_id(tile: &TerrainTile) -> Option<u8> {
+    BASE_TILES
+        .iter()
+        .position(|base| base == tile)
+        .map(|index| index as u8)
+}
+
+pub fn tile_by_id(id: u8) -> &'static TerrainTile {
+    BASE_TILES
+        .get(id as usize)
+        .unwrap_or(&BASE_TILES[UNKNOWN_ID as usize])
+}
+
+// Чанк, в который попадает клетка
+pub fn chunk_position(x: i32, y: i32) -> (i32, i32) {
+    (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE))
+}
+
+fn index(x: i32, y: i32) -> usize {
+    (y.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + x.rem_euclid(CHUNK_SIZE)) as usize
+}
+
+// Плотный блок клеток CHUNK_SIZE x CHUNK_SIZE. Координаты - мировые
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ChunkData")]
+pub struct Chunk {
+    tiles: Vec<u8>,
+}
+
+// Чанк из сохранения до проверки размера
+#[derive(Deserialize)]
+struct ChunkData {
+    tiles: Vec<u8>,
+}
+
+impl TryFrom<ChunkData> for Chunk {
+    type Error = String;
+
+    fn try_from(data: ChunkData) -> Result<Chunk, String> {
+        let expected = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+        if data.tiles.len() != expected {
+            return Err(format!(
+                "Чанк должен содержать {} клеток, а не {}",
+                expected,
+                data.tiles.len()
+            ));
+        }
+        Ok(Chunk { tiles: data.tiles })
+    }
+}
+
+impl Chunk {
+    pub fn filled(tile: &TerrainTile) -> Chunk {
+        let id = tile_id(tile).unwrap_or(UNKNOWN_ID);
+        Chunk {
+            tiles: vec![id; (CHUNK_SIZE * CHUNK_SIZE) as usize],
+        }
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> &'static TerrainTile {
+        tile_by_id(self.tiles[index(x, y)])
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, tile: &TerrainTile) {
+        self.tiles[index(x, y)] = tile_id(tile).unwrap_or(UNKNOWN_ID);
+    }
+}
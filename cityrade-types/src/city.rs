@@ -4,11 +4,55 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{
+    adjacency::{self, AdjacencyBonus, AdjacencyEffect},
+    budget::{BudgetItem, BudgetReport, TAX_HAPPINESS_PENALTY, TaxPolicy},
     building::{Building, BuildingType},
+    construction::{BuildQueue, ConstructionKind, ConstructionProject},
+    content,
+    item::{CraftingJob, GoodType, Inventory},
+    labor::{Staffing, assign_workers},
+    ledger::{LedgerEntry, LedgerReason},
+    market::Market,
+    mobility::{
+        ClassConversion, ConversionRequirement, ConversionRule, MERCHANTS_PER_MARKET_LEVEL,
+        Mobility,
+    },
+    needs::{self, HappinessBreakdown, HappinessFactor},
+    population::{Population, PopulationClass},
+    power::{self, PowerReport},
+    production::{ProductionBreakdown, ProductionSource},
     resources::{ResourceType, Resources},
+    storage::{self, StorageReport},
+    technology::{CityStat, ResearchEvent, ResearchState, TechEffect, TechnologyType},
+    world::{self, Deposit, WorldMap},
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Очки исследований, которые город получает без лабораторий
+const BASE_RESEARCH_POINTS: u32 = 2;
+// Вес бонуса классов населения к производству
+const POPULATION_BONUS_WEIGHT: f32 = 0.1;
+// Город занимает месторождения не дальше этого числа клеток от центра
+pub const DEPOSIT_CLAIM_RADIUS: i32 = 3;
+// Здание разрабатывает месторождения не дальше этого числа клеток от себя
+pub const DEPOSIT_WORK_RADIUS: i32 = 1;
+
+// Ресурсы, которые проходят через конвейер производства
+pub const PRODUCED_RESOURCES: [ResourceType; 7] = [
+    ResourceType::Gold,
+    ResourceType::Wood,
+    ResourceType::Stone,
+    ResourceType::Food,
+    ResourceType::Iron,
+    ResourceType::Crystal,
+    ResourceType::Energy,
+];
+// Снижение целевого счастья класса при полной безработице
+const UNEMPLOYMENT_HAPPINESS_PENALTY: f32 = 0.3;
+// Влияние уровня храма и казарм на целевое счастье
+const TEMPLE_HAPPINESS: f32 = 0.05;
+const BARRACKS_UNHAPPINESS: f32 = 0.02;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Terrain {
     Plain,
     Forest,
@@ -20,57 +64,30 @@ pub enum Terrain {
 }
 
 impl Terrain {
-    pub fn display_name(&self) -> &str {
-        match self {
-            Terrain::Plain => "Равнина",
-            Terrain::Forest => "Лес",
-            Terrain::Mountain => "Горы",
-            Terrain::Desert => "Пустыня",
-            Terrain::Swamp => "Болото",
-            Terrain::Water => "Вода",
-            Terrain::Snow => "Снег",
-        }
+    pub fn all() -> [Terrain; 7] {
+        [
+            Terrain::Plain,
+            Terrain::Forest,
+            Terrain::Mountain,
+            Terrain::Desert,
+            Terrain::Swamp,
+            Terrain::Water,
+            Terrain::Snow,
+        ]
     }
 
-    pub fn resource_modifier(&self) -> HashMap<ResourceType, f32> {
-        let mut modifiers = HashMap::new();
-
-        match self {
-            Terrain::Plain => {
-                modifiers.insert(ResourceType::Food, 1.2);
-                modifiers.insert(ResourceType::Gold, 1.0);
-            }
-            Terrain::Forest => {
-                modifiers.insert(ResourceType::Wood, 1.5);
-                modifiers.insert(ResourceType::Food, 0.8);
-            }
-            Terrain::Mountain => {
-                modifiers.insert(ResourceType::Stone, 1.5);
-                modifiers.insert(ResourceType::Iron, 1.3);
-                modifiers.insert(ResourceType::Crystal, 1.2);
-                modifiers.insert(ResourceType::Food, 0.6);
-            }
-            Terrain::Desert => {
-                modifiers.insert(ResourceType::Crystal, 1.3);
-                modifiers.insert(ResourceType::Food, 0.5);
-                modifiers.insert(ResourceType::Wood, 0.3);
-            }
-            Terrain::Swamp => {
-                modifiers.insert(ResourceType::Wood, 1.1);
-                modifiers.insert(ResourceType::Food, 0.7);
-            }
-            Terrain::Water => {
-                modifiers.insert(ResourceType::Food, 1.3);
-                modifiers.insert(ResourceType::Gold, 1.1);
-            }
-            Terrain::Snow => {
-                modifiers.insert(ResourceType::Crystal, 1.4);
-                modifiers.insert(ResourceType::Food, 0.4);
-                modifiers.insert(ResourceType::Energy, 0.8);
-            }
+    pub fn display_name(&self) -> String {
+        match content::current().terrain(self) {
+            Some(definition) => definition.display_name.clone(),
+            None => format!("{:?}", self),
         }
+    }
 
-        modifiers
+    pub fn resource_modifier(&self) -> HashMap<ResourceType, f32> {
+        content::current()
+            .terrain(self)
+            .map(|definition| definition.resource_modifiers.iter().cloned().collect())
+            .unwrap_or_default()
     }
 }
 
@@ -100,11 +117,25 @@ pub struct City {
     pub id: String,
     pub name: String,
     pub owner_id: String,
-    pub population: u32,
+    pub population: Population,
+    pub staffing: Staffing,
+    pub labor_priorities: HashMap<String, u32>, // ключ - id здания
+    pub power_priorities: HashMap<String, u32>, // ключ - id здания
+    pub power: PowerReport,                     // энергосеть за последний тик
+    pub mobility: Mobility,
     pub buildings: HashMap<String, Building>,
+    pub construction: BuildQueue,
     pub resources: Resources,
+    pub storage: StorageReport, // хранилища за последний тик
+    pub inventory: Inventory,   // товары из мастерских
+    pub crafting: Vec<CraftingJob>,
     pub stats: CityStats,
+    pub taxes: TaxPolicy,
+    pub budget: BudgetReport,            // золото за последний тик
+    pub production: ProductionBreakdown, // источники производства за последний тик
+    pub research: ResearchState,
     pub terrain: Terrain,
+    pub deposits: Vec<Deposit>, // Месторождения, занятые городом
     pub position: (i32, i32),
     pub created_at: DateTime<Utc>,
     pub last_updated: DateTime<Utc>,
@@ -116,78 +147,530 @@ impl City {
             id: Uuid::new_v4().to_string(),
             name,
             owner_id,
-            population: 10,
+            population: Population::new(),
+            staffing: Staffing::default(),
+            labor_priorities: HashMap::new(),
+            power_priorities: HashMap::new(),
+            power: PowerReport::default(),
+            mobility: Mobility::default(),
             buildings: HashMap::new(),
+            construction: BuildQueue::default(),
             resources: Resources::new(),
+            storage: StorageReport::default(),
+            inventory: Inventory::default(),
+            crafting: Vec::new(),
             stats: CityStats::default(),
+            taxes: TaxPolicy::default(),
+            budget: BudgetReport::default(),
+            production: ProductionBreakdown::default(),
+            research: ResearchState::new(),
             terrain,
+            deposits: Vec::new(),
             position,
             created_at: Utc::now(),
             last_updated: Utc::now(),
         }
     }
 
-    pub fn update(&mut self) {
+    // Игровой тик города. Возвращает технологии, изученные за этот тик
+    pub fn update(&mut self) -> Vec<ResearchEvent> {
+        // Продвигаем строительство
+        self.update_construction();
+
         // Обновляем ресурсы на основе зданий
         self.update_resource_production();
 
+        // Вкладываем очки исследований в очередь технологий
+        let research = self.update_research();
+
+        // Обслуживаем здания
+        self.update_maintenance();
+
         // Обновляем статистику
         self.update_stats();
 
         // Обновляем население
         self.update_population();
 
+        // Переводим жителей между классами
+        self.update_mobility();
+
+        // Мастерские изготавливают товары
+        self.update_crafting();
+
+        // Возобновляемые месторождения восстанавливаются
+        for deposit in self.deposits.iter_mut() {
+            deposit.regenerate();
+        }
+
+        // Еда портится, излишки не помещаются в хранилища
+        self.update_storage();
+
+        // Ресурс "Население" отражает фактическое число жителей
+        let citizens = self.population.total() + self.mobility.in_training();
+        let recorded = self.resources.get(&ResourceType::Population);
+        let entry = LedgerEntry::new(LedgerReason::Adjustment);
+        self.resources.apply_partial(if citizens >= recorded {
+            entry.credit(ResourceType::Population, citizens - recorded)
+        } else {
+            entry.debit(ResourceType::Population, recorded - citizens)
+        });
+
         // Обновляем временную метку
         self.last_updated = Utc::now();
+
+        research
     }
 
+    // Конвейер производства за один тик. Шаги выполняются строго по порядку:
+    //
+    // 1. База: стартовое производство города.
+    // 2. Здания: выработка каждого здания с учетом укомплектованности,
+    //    состояния, соседства и месторождений города. Потребление зданий
+    //    учитывается отдельно и модификаторы на него не действуют.
+    // 3. Энергия: выработка проходит через модификаторы (шаг 5) и
+    //    распределяется между потребителями; без энергии здания производят
+    //    пропорционально меньше.
+    // 4. Остальные ресурсы: выработка зданий с учетом энергоснабжения.
+    // 5. Модификаторы валовой выработки: местность, затем технологии, затем
+    //    бонусы классов населения. Округление выполняется один раз в конце.
+    // 6. Налоги добавляются к золоту после модификаторов.
+    // 7. Ограничения: итоговые скорости заменяют прежние целиком, а списание
+    //    при применении не превышает запас.
+    //
+    // Население не производится зданиями: ресурс "Население" отражает
+    // фактическое число жителей и выставляется в конце update
     pub fn update_resource_production(&mut self) {
-        // Сбрасываем производство к нулю
-        let mut production_rates = HashMap::new();
-        for resource_type in [
-            ResourceType::Gold,
-            ResourceType::Wood,
-            ResourceType::Stone,
-            ResourceType::Food,
-            ResourceType::Iron,
-            ResourceType::Crystal,
-            ResourceType::Energy,
-        ] {
-            // Базовое производство
-            let base_rate = match resource_type {
+        let mut production = ProductionBreakdown::default();
+        let mut budget = BudgetReport::default();
+        // Валовая выработка и потребление зданий по ресурсам
+        let mut gross: HashMap<ResourceType, i32> = HashMap::new();
+        let mut upkeep: HashMap<ResourceType, i32> = HashMap::new();
+
+        // 1. Базовое производство
+        for resource in PRODUCED_RESOURCES {
+            let base_rate = match resource {
                 ResourceType::Gold => 5,
                 ResourceType::Wood => 3,
                 ResourceType::Stone => 2,
                 ResourceType::Food => 8,
                 _ => 0,
             };
-            production_rates.insert(resource_type, base_rate);
+            production.add(resource.clone(), ProductionSource::Base, base_rate);
+            gross.insert(resource, base_rate);
         }
+        budget.add_income(BudgetItem::BaseIncome, gross[&ResourceType::Gold]);
 
-        // Добавляем производство от зданий
-        for building in self.buildings.values() {
+        // 2. Распределяем жителей по рабочим местам и считаем выработку зданий
+        self.staffing = assign_workers(&self.population, &self.buildings, &self.labor_priorities);
+
+        let mut adjacency_bonuses: HashMap<(String, ResourceType), f32> = HashMap::new();
+        for bonus in self.adjacency_bonuses() {
+            if let (Some(building_id), AdjacencyEffect::Production { resource, bonus }) =
+                (bonus.building_id, bonus.effect)
+            {
+                *adjacency_bonuses
+                    .entry((building_id, resource))
+                    .or_insert(0.0) += bonus;
+            }
+        }
+
+        let mut buildings: Vec<&Building> = self.buildings.values().collect();
+        buildings.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut outputs = Vec::new();
+        let mut demands = Vec::new();
+        for building in buildings {
+            let ratio = self.staffing.ratio(&building.id) * building.condition_efficiency();
             for (resource, amount) in building.production_effect() {
-                *production_rates.entry(resource).or_insert(0) += amount;
+                if resource == ResourceType::Population {
+                    continue;
+                }
+                if amount > 0 {
+                    let bonus = adjacency_bonuses
+                        .get(&(building.id.clone(), resource.clone()))
+                        .unwrap_or(&0.0);
+                    // Прибавка от месторождений рядом со зданием, если они есть
+                    let extra = amount as f32 * ratio * building.building_type.deposit_bonus();
+                    outputs.push((
+                        building,
+                        resource,
+                        amount as f32 * ratio * (1.0 + bonus),
+                        extra,
+                    ));
+                } else if amount < 0 && resource == ResourceType::Energy {
+                    demands.push((
+                        building.id.clone(),
+                        building.building_type.clone(),
+                        amount.unsigned_abs(),
+                    ));
+                } else if amount < 0 {
+                    if resource == ResourceType::Gold {
+                        budget.add_expense(
+                            BudgetItem::Upkeep(building.building_type.clone()),
+                            -amount,
+                        );
+                    }
+                    production.add(
+                        resource.clone(),
+                        ProductionSource::Upkeep {
+                            building_id: building.id.clone(),
+                            building_type: building.building_type.clone(),
+                        },
+                        amount,
+                    );
+                    *upkeep.entry(resource).or_insert(0) -= amount;
+                }
+            }
+        }
+
+        // 3. Энергия не копится как обычный ресурс: выработка за тик и запас
+        // распределяются между потребителями по приоритетам
+        for (building, resource, amount, extra) in &outputs {
+            if *resource != ResourceType::Energy {
+                continue;
             }
+            let position = self.world_position(building.position);
+            let extracted = world::extract_from(
+                &mut self.deposits,
+                resource,
+                position,
+                DEPOSIT_WORK_RADIUS,
+                extra.round() as u32,
+            );
+            let amount = (amount + extracted as f32).round() as i32;
+            production.add(
+                ResourceType::Energy,
+                ProductionSource::Building {
+                    building_id: building.id.clone(),
+                    building_type: building.building_type.clone(),
+                },
+                amount,
+            );
+            *gross.entry(ResourceType::Energy).or_insert(0) += amount;
+        }
+        let generation = self.modify_production(
+            &ResourceType::Energy,
+            gross[&ResourceType::Energy],
+            &mut production,
+        );
+        let stored = self.resources.get(&ResourceType::Energy);
+        self.power = power::distribute(
+            generation.max(0) as u32,
+            stored,
+            demands,
+            &self.power_priorities,
+        );
+        for consumer in &self.power.consumers {
+            production.add(
+                ResourceType::Energy,
+                ProductionSource::Upkeep {
+                    building_id: consumer.building_id.clone(),
+                    building_type: consumer.building_type.clone(),
+                },
+                -(consumer.supplied as i32),
+            );
         }
 
-        // Применяем модификаторы местности
-        let terrain_modifiers = self.terrain.resource_modifier();
-        for (resource, modifier) in terrain_modifiers {
-            if let Some(amount) = production_rates.get_mut(&resource) {
-                *amount = (*amount as f32 * modifier) as i32;
+        // 4. Без энергии здания производят пропорционально меньше. Прибавка от
+        // месторождений добывается из ближайших к зданию, уже с учетом
+        // энергии, и не может превысить их запас
+        for (building, resource, amount, extra) in outputs {
+            if resource == ResourceType::Energy {
+                continue;
             }
+            let ratio = self.power.ratio(&building.id);
+            let position = self.world_position(building.position);
+            let extracted = world::extract_from(
+                &mut self.deposits,
+                &resource,
+                position,
+                DEPOSIT_WORK_RADIUS,
+                (extra * ratio).round() as u32,
+            );
+            let amount = (amount * ratio + extracted as f32).round() as i32;
+            if resource == ResourceType::Gold && amount > 0 {
+                budget.add_income(BudgetItem::Building(building.building_type.clone()), amount);
+            }
+            production.add(
+                resource.clone(),
+                ProductionSource::Building {
+                    building_id: building.id.clone(),
+                    building_type: building.building_type.clone(),
+                },
+                amount,
+            );
+            *gross.entry(resource).or_insert(0) += amount;
         }
 
-        // Устанавливаем новые значения производства
-        for (resource, rate) in production_rates {
-            self.resources.set_production_rate(resource, rate);
+        // 5. Модификаторы валовой выработки, затем потребление зданий
+        let mut production_rates = HashMap::new();
+        for resource in PRODUCED_RESOURCES {
+            if resource == ResourceType::Energy {
+                continue;
+            }
+            let produced = self.modify_production(&resource, gross[&resource], &mut production);
+            if resource == ResourceType::Gold {
+                let modifiers = produced - gross[&resource];
+                if modifiers >= 0 {
+                    budget.add_income(BudgetItem::Modifiers, modifiers);
+                } else {
+                    budget.add_expense(BudgetItem::Modifiers, -modifiers);
+                }
+            }
+            let consumed = *upkeep.get(&resource).unwrap_or(&0);
+            production_rates.insert(resource, produced - consumed);
         }
 
-        // Применяем производство к текущим ресурсам
+        // 6. Налоги не зависят от модификаторов производства
+        for (class, amount) in self.taxes.collect(&self.population) {
+            production.add(
+                ResourceType::Gold,
+                ProductionSource::Taxes(class.clone()),
+                amount,
+            );
+            budget.add_income(BudgetItem::Taxes(class), amount);
+        }
+        production_rates.insert(ResourceType::Gold, budget.balance());
+        production_rates.insert(ResourceType::Energy, self.power.net());
+        self.budget = budget;
+        self.production = production;
+
+        // 7. Новые скорости заменяют прежние, списание ограничено запасом
+        self.resources.set_production_rates(production_rates);
         self.resources.update_production();
     }
 
+    // Применяет к валовой выработке ресурса множители местности, технологий
+    // и классов населения. Вклад каждого множителя записывается отдельно, а
+    // округляется только накопленное значение, поэтому вклады в сумме дают итог
+    fn modify_production(
+        &self,
+        resource: &ResourceType,
+        gross: i32,
+        production: &mut ProductionBreakdown,
+    ) -> i32 {
+        let terrain = *self
+            .terrain
+            .resource_modifier()
+            .get(resource)
+            .unwrap_or(&1.0);
+        let mut multipliers = vec![(ProductionSource::Terrain, terrain)];
+        for effect in self.technology_effects() {
+            if let TechEffect::ProductionMultiplier {
+                resource: boosted,
+                multiplier,
+            } = effect
+                && &boosted == resource
+            {
+                multipliers.push((ProductionSource::Technology, multiplier));
+            }
+        }
+        let bonus = self.population.get_production_bonus(resource);
+        multipliers.push((
+            ProductionSource::Population,
+            1.0 + bonus * POPULATION_BONUS_WEIGHT,
+        ));
+
+        let mut amount = gross as f32;
+        let mut rounded = gross;
+        for (source, multiplier) in multipliers {
+            amount *= multiplier;
+            let next = amount.round() as i32;
+            production.add(resource.clone(), source, next - rounded);
+            rounded = next;
+        }
+        rounded
+    }
+
+    // Сколько заказов мастерские выполняют одновременно
+    pub fn crafting_capacity(&self) -> usize {
+        let capacity: f32 = self
+            .buildings
+            .values()
+            .filter(|b| b.building_type == BuildingType::Workshop)
+            .map(|b| {
+                let ratio = self.staffing.ratio(&b.id) * self.power.ratio(&b.id);
+                b.level as f32 * ratio * b.condition_efficiency()
+            })
+            .sum();
+        capacity.round() as usize
+    }
+
+    // Заказывает в мастерской партии товара. Сырье списывается сразу
+    pub fn craft(&mut self, good: GoodType, batches: u32) -> Result<String, String> {
+        if batches == 0 {
+            return Err("Количество партий должно быть положительным".to_string());
+        }
+        if !self
+            .buildings
+            .values()
+            .any(|b| b.building_type == BuildingType::Workshop)
+        {
+            return Err("Для изготовления товаров нужна мастерская".to_string());
+        }
+
+        let recipe = good
+            .recipe()
+            .ok_or_else(|| format!("Нет рецепта для {:?}", good))?;
+        let inputs = recipe.inputs_for(batches);
+        self.resources
+            .apply(LedgerEntry::cost(LedgerReason::Crafting, &inputs))?;
+
+        let job = CraftingJob::new(&recipe, batches);
+        let id = job.id.clone();
+        self.crafting.push(job);
+        Ok(id)
+    }
+
+    // Продвигает заказы мастерских и складывает готовые товары
+    pub fn update_crafting(&mut self) {
+        let capacity = self.crafting_capacity().min(self.crafting.len());
+        for job in self.crafting[..capacity].iter_mut() {
+            job.progress += 1;
+        }
+
+        let (finished, in_progress): (Vec<CraftingJob>, Vec<CraftingJob>) =
+            self.crafting.drain(..).partition(|job| job.is_complete());
+        self.crafting = in_progress;
+
+        for job in finished {
+            self.inventory.add(&job.good, job.quantity);
+        }
+    }
+
+    // Продает товары со склада города на рынке
+    pub fn sell_goods(
+        &mut self,
+        market: &mut Market,
+        good: &GoodType,
+        quantity: u32,
+    ) -> Result<u32, String> {
+        if self.inventory.get(good) < quantity {
+            return Err("Недостаточно товаров".to_string());
+        }
+
+        let revenue = market.sell_good(good, quantity)?;
+        self.inventory.subtract(good, quantity);
+        self.resources.apply_partial(
+            LedgerEntry::new(LedgerReason::Trade).credit(ResourceType::Gold, revenue),
+        );
+        Ok(revenue)
+    }
+
+    // Покупает товары на рынке за золото города
+    pub fn buy_goods(
+        &mut self,
+        market: &mut Market,
+        good: &GoodType,
+        quantity: u32,
+    ) -> Result<u32, String> {
+        let price = market
+            .goods
+            .get(good)
+            .map(|listing| listing.current_price.saturating_mul(quantity))
+            .ok_or_else(|| "Товар не найден на рынке".to_string())?;
+        if self.resources.get(&ResourceType::Gold) < price {
+            return Err("Недостаточно золота".to_string());
+        }
+
+        let price = market.buy_good(good, quantity)?;
+        self.resources
+            .apply(LedgerEntry::new(LedgerReason::Trade).debit(ResourceType::Gold, price))?;
+        self.inventory.add(good, quantity);
+        Ok(price)
+    }
+
+    // Платит за содержание зданий и изнашивает их. Здания обслуживаются по
+    // порядку идентификаторов, пока хватает ресурсов. Расходы за ход
+    // проводятся одной записью журнала
+    pub fn update_maintenance(&mut self) {
+        let mut ids: Vec<String> = self.buildings.keys().cloned().collect();
+        ids.sort();
+
+        let mut remaining: HashMap<ResourceType, u32> = HashMap::new();
+        let mut total = LedgerEntry::new(LedgerReason::Maintenance);
+        for id in ids {
+            let building = &self.buildings[&id];
+            let building_type = building.building_type.clone();
+            let costs = building.maintenance();
+            let maintained = costs.iter().all(|(resource, amount)| {
+                *remaining
+                    .entry(resource.clone())
+                    .or_insert_with(|| self.resources.get(resource))
+                    >= *amount
+            });
+
+            if maintained {
+                for (resource, amount) in &costs {
+                    if let Some(left) = remaining.get_mut(resource) {
+                        *left -= amount;
+                    }
+                    total = total.debit(resource.clone(), *amount);
+                }
+                let gold = costs
+                    .iter()
+                    .filter(|(resource, _)| *resource == ResourceType::Gold)
+                    .map(|(_, amount)| *amount as i32)
+                    .sum();
+                self.budget
+                    .add_expense(BudgetItem::Maintenance(building_type), gold);
+            }
+
+            if let Some(building) = self.buildings.get_mut(&id) {
+                building.decay(maintained);
+            }
+        }
+        // Списание заранее проверено по остаткам, поэтому ничего не обрезается
+        self.resources.apply_partial(total);
+    }
+
+    // Полностью восстанавливает состояние здания за ресурсы
+    pub fn repair_building(
+        &mut self,
+        building_id: &str,
+    ) -> Result<Vec<(ResourceType, u32)>, String> {
+        let building = match self.buildings.get(building_id) {
+            Some(b) => b,
+            None => return Err("Здание не найдено".to_string()),
+        };
+
+        let costs = building.repair_cost();
+        if costs.is_empty() {
+            return Err("Здание не нуждается в ремонте".to_string());
+        }
+        self.resources
+            .apply(LedgerEntry::cost(LedgerReason::Repair, &costs))?;
+
+        if let Some(building) = self.buildings.get_mut(building_id) {
+            building.repair();
+        }
+        Ok(costs)
+    }
+
+    pub fn update_storage(&mut self) {
+        let capacities = storage::capacities(self.buildings.values());
+        for (resource, capacity) in &capacities {
+            self.resources.set_capacity(resource.clone(), *capacity);
+        }
+
+        let mut spoiled = HashMap::new();
+        let food = self.resources.get(&ResourceType::Food);
+        let rotten = (food as f32 * storage::spoilage_rate(self.buildings.values())) as u32;
+        if rotten > 0 {
+            self.resources.apply_partial(
+                LedgerEntry::new(LedgerReason::Spoilage).debit(ResourceType::Food, rotten),
+            );
+            spoiled.insert(ResourceType::Food, rotten);
+        }
+
+        self.storage = StorageReport {
+            capacity: capacities,
+            overflow: self.resources.take_overflow(),
+            spoiled,
+        };
+    }
+
     pub fn update_stats(&mut self) {
         // Сбрасываем статистику к базовым значениям
         self.stats = CityStats::default();
@@ -199,7 +682,6 @@ impl City {
                     self.stats.max_population += 10 * building.level;
                 }
                 BuildingType::Temple => {
-                    self.stats.happiness += 5 * building.level;
                     self.stats.culture += 3 * building.level;
                 }
                 BuildingType::Wall => {
@@ -210,113 +692,548 @@ impl City {
                 }
                 BuildingType::Barracks => {
                     self.stats.defense += 10 * building.level;
-                    self.stats.happiness -= 2 * building.level;
                 }
                 _ => {}
             }
         }
 
+        // Счастье города - среднее счастье его жителей
+        self.stats.happiness = (self.population.happiness * 100.0).round() as u32;
+
         // Увеличиваем максимальное количество зданий на основе населения
-        self.stats.max_buildings = 5 + (self.population / 20);
+        self.stats.max_buildings = 5 + (self.population.total() / 20);
+
+        // Бонусы к характеристикам от изученных технологий
+        for effect in self.technology_effects() {
+            if let TechEffect::StatBonus { stat, amount } = effect {
+                let value = match stat {
+                    // Учитывается в факторах счастья классов
+                    CityStat::Happiness => continue,
+                    CityStat::Defense => &mut self.stats.defense,
+                    CityStat::Culture => &mut self.stats.culture,
+                    CityStat::MaxPopulation => &mut self.stats.max_population,
+                    CityStat::MaxBuildings => &mut self.stats.max_buildings,
+                };
+                *value = value.saturating_add_signed(amount);
+            }
+        }
     }
 
     pub fn update_population(&mut self) {
-        // Рост населения зависит от счастья и наличия еды
-        let food = self.resources.get(&ResourceType::Food);
+        // Каждый класс потребляет свою корзину ресурсов из запасов города
+        let satisfaction = needs::consume(&self.population, &mut self.resources);
 
-        // Если еды недостаточно, уменьшаем население
-        if food < self.population {
-            self.decrease_population(1);
-            self.stats.happiness = self.stats.happiness.saturating_sub(5);
-        } else {
-            // Иначе, увеличиваем население с вероятностью, зависящей от счастья
-            let growth_chance = (self.stats.happiness as f32) / 100.0;
+        let classes: Vec<PopulationClass> = self.population.classes.keys().cloned().collect();
+        for class in classes {
+            let factors = self.happiness_factors(&class, &satisfaction);
+            self.population.apply_happiness(class, factors);
+        }
 
-            if rand::random::<f32>() < growth_chance && self.population < self.stats.max_population
-            {
-                self.increase_population(1);
-            }
+        // Рост зависит от счастья и жилья (максимального населения города)
+        self.population.update(self.stats.max_population);
+    }
+
+    // Все факторы, из которых складывается целевое счастье класса
+    pub fn happiness_factors(
+        &self,
+        class: &PopulationClass,
+        satisfaction: &HashMap<ResourceType, f32>,
+    ) -> Vec<(HappinessFactor, f32)> {
+        let mut factors =
+            needs::needs_factors(class, self.population.food_consumption, satisfaction);
+
+        let level_of = |building_type: BuildingType| -> u32 {
+            self.buildings
+                .values()
+                .filter(|b| b.building_type == building_type)
+                .map(|b| b.level)
+                .sum()
+        };
+
+        let temples = level_of(BuildingType::Temple);
+        if temples > 0 {
+            factors.push((HappinessFactor::Temples, temples as f32 * TEMPLE_HAPPINESS));
+        }
+
+        // Солдатам казармы не мешают
+        let barracks = level_of(BuildingType::Barracks);
+        if barracks > 0 && *class != PopulationClass::Soldier {
+            factors.push((
+                HappinessFactor::Barracks,
+                -(barracks as f32) * BARRACKS_UNHAPPINESS,
+            ));
+        }
+
+        let adjacency: f32 = self
+            .adjacency_bonuses()
+            .into_iter()
+            .filter_map(|bonus| match bonus.effect {
+                AdjacencyEffect::Happiness(amount) => Some(amount),
+                _ => None,
+            })
+            .sum();
+        if adjacency != 0.0 {
+            factors.push((HappinessFactor::Adjacency, adjacency));
+        }
+
+        let count = self.population.count(class);
+        let unemployed = *self.staffing.unemployed.get(class).unwrap_or(&0);
+        if count > 0 && unemployed > 0 {
+            factors.push((
+                HappinessFactor::Unemployment,
+                -(unemployed as f32 / count as f32) * UNEMPLOYMENT_HAPPINESS_PENALTY,
+            ));
         }
+
+        let tax_rate = self.taxes.rate(class);
+        if tax_rate > 0.0 {
+            factors.push((HappinessFactor::Taxes, -tax_rate * TAX_HAPPINESS_PENALTY));
+        }
+
+        let technology: i32 = self
+            .technology_effects()
+            .into_iter()
+            .filter_map(|effect| match effect {
+                TechEffect::StatBonus {
+                    stat: CityStat::Happiness,
+                    amount,
+                } => Some(amount),
+                _ => None,
+            })
+            .sum();
+        if technology != 0 {
+            factors.push((HappinessFactor::Technology, technology as f32 / 100.0));
+        }
+
+        factors
     }
 
+    // Разбор последнего изменения счастья класса
+    pub fn happiness_breakdown(&self, class: &PopulationClass) -> Option<&HappinessBreakdown> {
+        self.population.happiness_breakdown.get(class)
+    }
+
+    // Начинает перевод жителей в другой класс. Стоимость списывается сразу,
+    // жители выбывают из прежнего класса до окончания обучения
+    pub fn train_citizens(
+        &mut self,
+        from: PopulationClass,
+        to: PopulationClass,
+        count: u32,
+    ) -> Result<String, String> {
+        if count == 0 {
+            return Err("Количество жителей должно быть положительным".to_string());
+        }
+
+        let rule = ConversionRule::find(&from, &to)
+            .ok_or_else(|| format!("Перевод из {:?} в {:?} невозможен", from, to))?;
+
+        if !self.meets_requirement(&rule.requirement) {
+            return Err(format!(
+                "Не выполнено условие перевода: {:?}",
+                rule.requirement
+            ));
+        }
+
+        if self.population.count(&from) < count {
+            return Err(format!("Недостаточно жителей класса {:?}", from));
+        }
+
+        let cost = rule.total_cost(count);
+        self.resources
+            .apply(LedgerEntry::cost(LedgerReason::Training, &cost))?;
+
+        self.population.remove_citizens(&from, count);
+        let conversion = ClassConversion::new(&rule, count);
+        let id = conversion.id.clone();
+        self.mobility.conversions.push(conversion);
+
+        Ok(id)
+    }
+
+    // Прерывает обучение. Жители возвращаются в прежний класс, ресурсы не возвращаются
+    pub fn cancel_training(&mut self, conversion_id: &str) -> Result<(), String> {
+        let index = self
+            .mobility
+            .conversions
+            .iter()
+            .position(|c| c.id == conversion_id)
+            .ok_or_else(|| "Обучение не найдено".to_string())?;
+
+        let conversion = self.mobility.conversions.remove(index);
+        self.population
+            .add_citizens(conversion.from, conversion.count);
+        Ok(())
+    }
+
+    pub fn update_mobility(&mut self) {
+        for conversion in self.mobility.tick() {
+            self.population
+                .add_citizens(conversion.to, conversion.count);
+        }
+
+        // Работающие рынки постепенно превращают крестьян в торговцев
+        let market_activity: f32 = self
+            .buildings
+            .values()
+            .filter(|b| matches!(b.building_type, BuildingType::Market))
+            .map(|b| b.level as f32 * self.staffing.ratio(&b.id))
+            .sum();
+        self.mobility.merchant_progress += market_activity * MERCHANTS_PER_MARKET_LEVEL;
+
+        let emerged = (self.mobility.merchant_progress as u32)
+            .min(self.population.count(&PopulationClass::Peasant));
+        if emerged > 0 {
+            self.mobility.merchant_progress -= emerged as f32;
+            self.population
+                .remove_citizens(&PopulationClass::Peasant, emerged);
+            self.population
+                .add_citizens(PopulationClass::Merchant, emerged);
+        }
+        // Без крестьян прогресс не копится бесконечно
+        self.mobility.merchant_progress = self.mobility.merchant_progress.min(1.0);
+    }
+
+    pub fn meets_requirement(&self, requirement: &ConversionRequirement) -> bool {
+        match requirement {
+            ConversionRequirement::None => true,
+            ConversionRequirement::Building(building_type) => self
+                .buildings
+                .values()
+                .any(|b| &b.building_type == building_type),
+            ConversionRequirement::Technology(tech_type) => self.is_researched(tech_type),
+            ConversionRequirement::Culture(level) => self.stats.culture >= *level,
+        }
+    }
+
+    pub fn set_tax_rate(&mut self, class: PopulationClass, rate: f32) -> Result<(), String> {
+        self.taxes.set_rate(class, rate)
+    }
+
+    // Задает приоритет энергоснабжения здания: при нехватке энергии здания
+    // с большим приоритетом получают ее первыми
+    pub fn set_power_priority(&mut self, building_id: &str, priority: u32) -> Result<(), String> {
+        if !self.buildings.contains_key(building_id) {
+            return Err("Здание не найдено".to_string());
+        }
+
+        self.power_priorities
+            .insert(building_id.to_string(), priority);
+        Ok(())
+    }
+
+    // Задает приоритет укомплектования здания: чем больше, тем раньше
+    // здание получает работников
+    pub fn set_staffing_priority(
+        &mut self,
+        building_id: &str,
+        priority: u32,
+    ) -> Result<(), String> {
+        if !self.buildings.contains_key(building_id) {
+            return Err("Здание не найдено".to_string());
+        }
+
+        self.labor_priorities
+            .insert(building_id.to_string(), priority);
+        Ok(())
+    }
+
+    // Очки исследований за тик: базовые, от лабораторий и ученых
+    pub fn research_points(&self) -> u32 {
+        let laboratories: u32 = self
+            .buildings
+            .values()
+            .filter(|b| matches!(b.building_type, BuildingType::Laboratory))
+            .map(|b| {
+                let ratio = self.staffing.ratio(&b.id) * self.power.ratio(&b.id);
+                (10.0 * b.level as f32 * ratio) as u32
+            })
+            .sum();
+        let scholars = self.population.count(&PopulationClass::Scholar) * 2;
+        BASE_RESEARCH_POINTS + laboratories + scholars
+    }
+
+    pub fn start_research(&mut self, tech_type: TechnologyType) -> Result<(), String> {
+        self.research
+            .enqueue(&content::current().technologies, tech_type)
+    }
+
+    pub fn update_research(&mut self) -> Vec<ResearchEvent> {
+        let points = self.research_points();
+        self.research
+            .add_progress(&content::current().technologies, points)
+    }
+
+    // Эффекты всех технологий, изученных городом
+    pub fn technology_effects(&self) -> Vec<TechEffect> {
+        content::current()
+            .technologies
+            .effects_of(&self.research.completed)
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_researched(&self, tech_type: &TechnologyType) -> bool {
+        self.research.is_researched(tech_type)
+    }
+
+    // Стоимость строительства с учетом технологических скидок
+    pub fn construction_cost(&self, costs: Vec<(ResourceType, u32)>) -> Vec<(ResourceType, u32)> {
+        let discount: f32 = self
+            .technology_effects()
+            .into_iter()
+            .filter_map(|effect| match effect {
+                TechEffect::ConstructionDiscount(discount) => Some(discount),
+                _ => None,
+            })
+            .sum();
+        let factor = 1.0 - discount.clamp(0.0, 0.9);
+
+        costs
+            .into_iter()
+            .map(|(resource, amount)| (resource, (amount as f32 * factor).ceil() as u32))
+            .collect()
+    }
+
+    // Эффекты соседства между построенными зданиями города
+    pub fn adjacency_bonuses(&self) -> Vec<AdjacencyBonus> {
+        adjacency::bonuses(&content::current().adjacency, &self.buildings)
+    }
+
+    // Какие бонусы соседства даст здание, поставленное в эту клетку
+    pub fn preview_adjacency(
+        &self,
+        building_type: &BuildingType,
+        position: (i32, i32),
+    ) -> Vec<AdjacencyBonus> {
+        adjacency::preview(
+            &content::current().adjacency,
+            &self.buildings,
+            building_type,
+            position,
+        )
+    }
+
+    // Ставит здание в очередь строительства. Ресурсы списываются сразу,
+    // здание появится в городе с возвращенным идентификатором
     pub fn add_building(
         &mut self,
         building_type: BuildingType,
         name: String,
         position: (i32, i32),
     ) -> Result<String, String> {
-        // Проверка, не превышено ли максимальное количество зданий
-        if self.buildings.len() >= self.stats.max_buildings as usize {
+        // Проверка, не превышено ли максимальное количество зданий с учетом строящихся
+        if self.buildings.len() + self.construction.pending_buildings()
+            >= self.stats.max_buildings as usize
+        {
             return Err("Достигнут предел количества зданий".to_string());
         }
 
-        // Проверка, нет ли уже здания в этой позиции
-        for building in self.buildings.values() {
-            if building.position == position {
-                return Err("В этой позиции уже есть здание".to_string());
-            }
+        // Проверка, изучена ли технология, открывающая здание
+        if let Some(tech) = content::current()
+            .technologies
+            .required_technology(&building_type)
+            && !self.is_researched(&tech.tech_type)
+        {
+            return Err(format!(
+                "Сначала нужно изучить технологию \"{}\"",
+                tech.name
+            ));
         }
 
-        // Проверка, хватает ли ресурсов
-        let costs = building_type.base_cost();
-        if !self.resources.can_afford(&costs) {
-            return Err("Недостаточно ресурсов".to_string());
+        // Проверка, нет ли уже здания или стройки в этой позиции
+        if self.buildings.values().any(|b| b.position == position)
+            || self.construction.is_position_reserved(position)
+        {
+            return Err("В этой позиции уже есть здание".to_string());
         }
 
-        // Снимаем ресурсы
-        self.resources.pay(&costs);
+        // Снимаем ресурсы, если их хватает
+        let costs = self.construction_cost(building_type.base_cost());
+        if !self.resources.pay(LedgerReason::Construction, &costs) {
+            return Err("Недостаточно ресурсов".to_string());
+        }
 
-        // Создаем новое здание
+        // Ставим стройку в очередь
         let id = Uuid::new_v4().to_string();
-        let building = Building::new(id.clone(), name, building_type, position);
-
-        // Добавляем здание
-        self.buildings.insert(id.clone(), building);
+        let kind = ConstructionKind::New {
+            building_id: id.clone(),
+            building_type: building_type.clone(),
+            name,
+            position,
+        };
+        self.construction
+            .push(ConstructionProject::new(kind, building_type, costs));
 
         Ok(id)
     }
 
-    pub fn upgrade_building(&mut self, building_id: &str) -> Result<(), String> {
+    // Ставит улучшение здания в очередь строительства
+    pub fn upgrade_building(&mut self, building_id: &str) -> Result<String, String> {
         // Проверяем, существует ли здание
         let building = match self.buildings.get(building_id) {
             Some(b) => b,
             None => return Err("Здание не найдено".to_string()),
         };
 
-        // Проверяем, хватает ли ресурсов
-        let costs = building.upgrade_cost();
-        if !self.resources.can_afford(&costs) {
+        if self.construction.is_queued(building_id) {
+            return Err("Здание уже улучшается".to_string());
+        }
+
+        // Проверяем, хватает ли товаров
+        let costs = self.construction_cost(building.upgrade_cost());
+        let goods = building.upgrade_goods();
+        if !self.inventory.can_afford(&goods) {
+            return Err("Недостаточно товаров".to_string());
+        }
+
+        let kind = ConstructionKind::Upgrade {
+            building_id: building_id.to_string(),
+            target_level: building.level + 1,
+        };
+        let mut project = ConstructionProject::new(kind, building.building_type.clone(), costs);
+        project.goods = goods;
+
+        // Снимаем ресурсы и товары
+        if !self
+            .resources
+            .pay(LedgerReason::Construction, &project.cost)
+        {
             return Err("Недостаточно ресурсов".to_string());
         }
+        self.inventory.pay(&project.goods);
 
-        // Снимаем ресурсы
-        self.resources.pay(&costs);
+        Ok(self.construction.push(project))
+    }
 
-        // Улучшаем здание
-        let building = self.buildings.get_mut(building_id).unwrap();
-        building.upgrade();
+    // Завершает готовые проекты и возвращает их
+    pub fn update_construction(&mut self) -> Vec<ConstructionProject> {
+        let finished = self.construction.tick();
 
-        Ok(())
+        for project in &finished {
+            match &project.kind {
+                ConstructionKind::New {
+                    building_id,
+                    building_type,
+                    name,
+                    position,
+                } => {
+                    let mut building = Building::new(
+                        building_id.clone(),
+                        name.clone(),
+                        building_type.clone(),
+                        *position,
+                    );
+                    building.add_investment(&project.cost);
+                    self.buildings.insert(building_id.clone(), building);
+                }
+                ConstructionKind::Upgrade { building_id, .. } => {
+                    if let Some(building) = self.buildings.get_mut(building_id) {
+                        building.upgrade();
+                        building.add_investment(&project.cost);
+                    }
+                }
+                ConstructionKind::Demolish { building_id, .. } => {
+                    if let Some(building) = self.buildings.remove(building_id) {
+                        self.resources.apply_partial(LedgerEntry::income(
+                            LedgerReason::Salvage,
+                            &building.salvage(),
+                        ));
+                    }
+                    self.labor_priorities.remove(building_id);
+                    self.power_priorities.remove(building_id);
+                }
+            }
+        }
+
+        finished
     }
 
-    pub fn remove_building(&mut self, building_id: &str) -> Result<(), String> {
-        // Проверяем, существует ли здание
-        if !self.buildings.contains_key(building_id) {
-            return Err("Здание не найдено".to_string());
+    // Отменяет проект и возвращает часть его стоимости
+    pub fn cancel_construction(
+        &mut self,
+        project_id: &str,
+    ) -> Result<Vec<(ResourceType, u32)>, String> {
+        let project = self
+            .construction
+            .cancel(project_id)
+            .ok_or_else(|| "Проект не найден".to_string())?;
+
+        let refund = project.refund();
+        self.resources
+            .apply_partial(LedgerEntry::income(LedgerReason::Refund, &refund));
+        for (good, amount) in project.refund_goods() {
+            self.inventory.add(&good, amount);
         }
 
-        // Удаляем здание
-        self.buildings.remove(building_id);
+        Ok(refund)
+    }
 
-        Ok(())
+    // Ставит снос здания в очередь строительства. Когда работы закончатся,
+    // город получит часть вложенных в здание ресурсов
+    pub fn demolish_building(&mut self, building_id: &str) -> Result<String, String> {
+        let building = match self.buildings.get(building_id) {
+            Some(b) => b,
+            None => return Err("Здание не найдено".to_string()),
+        };
+
+        if let Some(reason) = self.building_in_use(building_id) {
+            return Err(format!("Здание нельзя снести: {}", reason));
+        }
+
+        let kind = ConstructionKind::Demolish {
+            building_id: building_id.to_string(),
+            level: building.level,
+        };
+        let project = ConstructionProject::new(kind, building.building_type.clone(), Vec::new());
+
+        Ok(self.construction.push(project))
+    }
+
+    // Причина, по которой здание сейчас занято, или None
+    pub fn building_in_use(&self, building_id: &str) -> Option<String> {
+        let building = self.buildings.get(building_id)?;
+
+        if self.construction.is_queued(building_id) {
+            return Some("над зданием уже идут работы".to_string());
+        }
+
+        // Процессы, которые остановятся без последнего здания своего типа
+        let busy = match building.building_type {
+            BuildingType::Barracks if self.mobility.is_training(&PopulationClass::Soldier) => {
+                Some("в казармах обучаются солдаты")
+            }
+            BuildingType::Workshop if !self.crafting.is_empty() => {
+                Some("в мастерской выполняются заказы")
+            }
+            BuildingType::Laboratory if !self.research.queue.is_empty() => {
+                Some("в лаборатории идут исследования")
+            }
+            _ => None,
+        };
+        let last_of_type = self
+            .buildings
+            .values()
+            .filter(|b| b.building_type == building.building_type)
+            .count()
+            == 1;
+
+        match busy {
+            Some(reason) if last_of_type => Some(reason.to_string()),
+            _ => None,
+        }
     }
 
     pub fn increase_population(&mut self, amount: u32) {
-        self.population = (self.population + amount).min(self.stats.max_population);
+        let room = self
+            .stats
+            .max_population
+            .saturating_sub(self.population.total());
+        self.population.distribute_growth(amount.min(room));
     }
 
     pub fn decrease_population(&mut self, amount: u32) {
-        self.population = self.population.saturating_sub(amount);
+        self.population.distribute_decline(amount);
     }
 
     pub fn get_resource_report(&self) -> String {
@@ -338,6 +1255,26 @@ impl City {
         report
     }
 
+    pub fn get_construction_report(&self) -> String {
+        let mut report = format!(
+            "Строительство в городе {} ({} из {} бригад занято):\n",
+            self.name,
+            self.construction.active().len(),
+            self.construction.slots
+        );
+
+        for project in &self.construction.projects {
+            report.push_str(&format!(
+                "- {}: {}/{}\n",
+                project.describe(),
+                project.progress,
+                project.total_ticks
+            ));
+        }
+
+        report
+    }
+
     pub fn get_buildings_report(&self) -> String {
         let mut report = format!(
             "Здания города {} ({}/{})\n",
@@ -356,6 +1293,78 @@ impl City {
         report
     }
 
+    pub fn get_happiness_report(&self) -> String {
+        let mut report = format!("Счастье города {}:\n", self.name);
+
+        let mut classes: Vec<&PopulationClass> =
+            self.population.happiness_breakdown.keys().collect();
+        classes.sort_by_key(|class| format!("{:?}", class));
+        for class in classes {
+            let breakdown = &self.population.happiness_breakdown[class];
+            report.push_str(&format!("{:?}. {}", class, breakdown.report()));
+        }
+
+        report
+    }
+
+    pub fn get_storage_report(&self) -> String {
+        format!("Хранилища города {}:\n{}", self.name, self.storage.report())
+    }
+
+    pub fn get_power_report(&self) -> String {
+        format!("Энергосеть города {}:\n{}", self.name, self.power.report())
+    }
+
+    // Позиции зданий задаются относительно города, а месторождения лежат в
+    // координатах мира
+    pub fn world_position(&self, local: (i32, i32)) -> (i32, i32) {
+        (self.position.0 + local.0, self.position.1 + local.1)
+    }
+
+    // Занимает свободные месторождения вокруг города
+    pub fn claim_deposits(&mut self, world: &mut WorldMap) -> usize {
+        let claimed = world.claim_deposits(self.position, DEPOSIT_CLAIM_RADIUS);
+        let count = claimed.len();
+        self.deposits.extend(claimed);
+        count
+    }
+
+    pub fn get_deposit_report(&self) -> String {
+        let mut report = format!("Месторождения города {}:\n", self.name);
+        if self.deposits.is_empty() {
+            report.push_str("  Нет месторождений\n");
+        }
+        for deposit in &self.deposits {
+            report.push_str(&format!(
+                "  {} ({}, {}): {}/{}{}\n",
+                deposit.resource,
+                deposit.position.0,
+                deposit.position.1,
+                deposit.amount,
+                deposit.capacity,
+                if deposit.is_renewable() {
+                    format!(", +{} за тик", deposit.regeneration)
+                } else {
+                    String::new()
+                }
+            ));
+        }
+        report
+    }
+
+    // Источники производства всех ресурсов за последний тик
+    pub fn get_production_report(&self) -> String {
+        let mut report = format!("Производство города {}:\n", self.name);
+        for resource in PRODUCED_RESOURCES {
+            report.push_str(&self.production.report(&resource));
+        }
+        report
+    }
+
+    pub fn get_budget_report(&self) -> String {
+        format!("Бюджет города {}:\n{}", self.name, self.budget.report())
+    }
+
     pub fn get_stats_report(&self) -> String {
         format!(
             "Статистика города {}:\n\
@@ -366,7 +1375,7 @@ impl City {
              Местность: {}\n\
              Основан: {}\n",
             self.name,
-            self.population,
+            self.population.total(),
             self.stats.max_population,
             self.stats.happiness,
             self.stats.defense,
@@ -376,18 +1385,33 @@ impl City {
         )
     }
 
-    pub fn subtract_resources(&mut self, resource_type: &ResourceType, amount: u32) -> bool {
-        let current = self.resources.get(resource_type);
-        
-        if current >= amount {
-            self.resources.subtract(resource_type, amount);
-            true
-        } else {
-            false
-        }
+    pub fn subtract_resources(
+        &mut self,
+        reason: LedgerReason,
+        resource_type: &ResourceType,
+        amount: u32,
+    ) -> bool {
+        self.resources
+            .apply(LedgerEntry::new(reason).debit(resource_type.clone(), amount))
+            .is_ok()
     }
 
-    pub fn add_resources(&mut self, resource_type: &ResourceType, amount: u32) {
-        self.resources.add(resource_type, amount);
+    pub fn add_resources(
+        &mut self,
+        reason: LedgerReason,
+        resource_type: &ResourceType,
+        amount: u32,
+    ) {
+        self.resources
+            .apply_partial(LedgerEntry::new(reason).credit(resource_type.clone(), amount));
+    }
+
+    // Последние операции с ресурсами города, начиная с самой новой
+    pub fn get_ledger_report(&self, count: usize) -> String {
+        let mut report = String::from("Журнал ресурсов:\n");
+        for entry in self.resources.ledger().recent(count) {
+            report.push_str(&format!("  {}\n", entry));
+        }
+        report
     }
 }
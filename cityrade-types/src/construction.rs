@@ -0,0 +1,212 @@
+use crate::building::BuildingType;
+use crate::item::GoodType;
+use crate::resources::ResourceType;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Сколько проектов город строит одновременно
+pub const DEFAULT_BUILD_SLOTS: usize = 2;
+// Доля стоимости, возвращаемая при отмене начатого проекта
+pub const CANCEL_REFUND_FRACTION: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConstructionKind {
+    New {
+        building_id: String, // Идентификатор, который получит готовое здание
+        building_type: BuildingType,
+        name: String,
+        position: (i32, i32),
+    },
+    Upgrade {
+        building_id: String,
+        target_level: u32,
+    },
+    Demolish {
+        building_id: String,
+        level: u32,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstructionProject {
+    pub id: String,
+    pub kind: ConstructionKind,
+    pub building_type: BuildingType,
+    pub cost: Vec<(ResourceType, u32)>, // Уже оплаченная стоимость
+    #[serde(default)]
+    pub goods: Vec<(GoodType, u32)>, // Уже потраченные товары
+    pub total_ticks: u32,
+    pub progress: u32,
+}
+
+impl ConstructionProject {
+    pub fn new(
+        kind: ConstructionKind,
+        building_type: BuildingType,
+        cost: Vec<(ResourceType, u32)>,
+    ) -> Self {
+        let total_ticks = match &kind {
+            ConstructionKind::New { .. } => building_type.build_time(1),
+            ConstructionKind::Upgrade { target_level, .. } => {
+                building_type.build_time(*target_level)
+            }
+            ConstructionKind::Demolish { level, .. } => building_type.demolition_time(*level),
+        };
+
+        ConstructionProject {
+            id: Uuid::new_v4().to_string(),
+            total_ticks,
+            kind,
+            building_type,
+            cost,
+            goods: Vec::new(),
+            progress: 0,
+        }
+    }
+
+    pub fn building_id(&self) -> &str {
+        match &self.kind {
+            ConstructionKind::New { building_id, .. } => building_id,
+            ConstructionKind::Upgrade { building_id, .. } => building_id,
+            ConstructionKind::Demolish { building_id, .. } => building_id,
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.progress > 0
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.progress >= self.total_ticks
+    }
+
+    // Доля выполненной работы от 0.0 до 1.0
+    pub fn progress_ratio(&self) -> f32 {
+        if self.total_ticks == 0 {
+            return 1.0;
+        }
+        (self.progress as f32 / self.total_ticks as f32).min(1.0)
+    }
+
+    // Ресурсы, которые вернутся при отмене: полностью до начала работ,
+    // частично после
+    pub fn refund(&self) -> Vec<(ResourceType, u32)> {
+        if !self.is_started() {
+            return self.cost.clone();
+        }
+
+        self.cost
+            .iter()
+            .map(|(resource, amount)| {
+                (
+                    resource.clone(),
+                    (*amount as f32 * CANCEL_REFUND_FRACTION) as u32,
+                )
+            })
+            .collect()
+    }
+
+    // Товары, которые вернутся при отмене, по тем же правилам, что и ресурсы
+    pub fn refund_goods(&self) -> Vec<(GoodType, u32)> {
+        if !self.is_started() {
+            return self.goods.clone();
+        }
+
+        self.goods
+            .iter()
+            .map(|(good, amount)| {
+                (
+                    good.clone(),
+                    (*amount as f32 * CANCEL_REFUND_FRACTION) as u32,
+                )
+            })
+            .collect()
+    }
+
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            ConstructionKind::New { name, .. } => format!(
+                "Строительство: {} ({})",
+                name,
+                self.building_type.display_name()
+            ),
+            ConstructionKind::Upgrade { target_level, .. } => format!(
+                "Улучшение: {} до уровня {}",
+                self.building_type.display_name(),
+                target_level
+            ),
+            ConstructionKind::Demolish { .. } => {
+                format!("Снос: {}", self.building_type.display_name())
+            }
+        }
+    }
+}
+
+// Очередь строительства города. Работы идут только в первых `slots` проектах
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildQueue {
+    pub projects: Vec<ConstructionProject>,
+    pub slots: usize,
+}
+
+impl Default for BuildQueue {
+    fn default() -> Self {
+        BuildQueue {
+            projects: Vec::new(),
+            slots: DEFAULT_BUILD_SLOTS,
+        }
+    }
+}
+
+impl BuildQueue {
+    pub fn push(&mut self, project: ConstructionProject) -> String {
+        let id = project.id.clone();
+        self.projects.push(project);
+        id
+    }
+
+    // Проекты, над которыми сейчас идут работы
+    pub fn active(&self) -> &[ConstructionProject] {
+        &self.projects[..self.projects.len().min(self.slots)]
+    }
+
+    pub fn get(&self, project_id: &str) -> Option<&ConstructionProject> {
+        self.projects.iter().find(|p| p.id == project_id)
+    }
+
+    pub fn is_queued(&self, building_id: &str) -> bool {
+        self.projects.iter().any(|p| p.building_id() == building_id)
+    }
+
+    // Новые здания, которые еще строятся
+    pub fn pending_buildings(&self) -> usize {
+        self.projects
+            .iter()
+            .filter(|p| matches!(p.kind, ConstructionKind::New { .. }))
+            .count()
+    }
+
+    pub fn is_position_reserved(&self, position: (i32, i32)) -> bool {
+        self.projects.iter().any(|p| {
+            matches!(&p.kind, ConstructionKind::New { position: reserved, .. } if *reserved == position)
+        })
+    }
+
+    // Продвигает работы на один тик и возвращает завершенные проекты
+    pub fn tick(&mut self) -> Vec<ConstructionProject> {
+        let slots = self.slots.min(self.projects.len());
+        for project in self.projects[..slots].iter_mut() {
+            project.progress += 1;
+        }
+
+        let (finished, remaining) = self.projects.drain(..).partition(|p| p.is_complete());
+        self.projects = remaining;
+
+        finished
+    }
+
+    pub fn cancel(&mut self, project_id: &str) -> Option<ConstructionProject> {
+        let index = self.projects.iter().position(|p| p.id == project_id)?;
+        Some(self.projects.remove(index))
+    }
+}
@@ -0,0 +1,451 @@
+use crate::adjacency::{AdjacencyEffect, AdjacencyRule};
+use crate::building::BuildingType;
+use crate::city::Terrain;
+use crate::item::{GoodDefinition, GoodType, Recipe};
+use crate::labor::WorkerSlot;
+use crate::resources::ResourceType;
+use crate::technology::{TechEffect, Technology, TechnologyTree};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+
+// Базовый набор контента, встроенный в сборку
+const BASE_CONTENT: &str = include_str!("../content/base.ron");
+
+static CURRENT: LazyLock<RwLock<Arc<GameContent>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(GameContent::base())));
+
+// Текущий активный контент. Снимок не меняется при горячей перезагрузке
+pub fn current() -> Arc<GameContent> {
+    CURRENT.read().unwrap().clone()
+}
+
+// Заменяет активный контент для всех последующих вызовов `current`
+pub fn install(content: GameContent) {
+    *CURRENT.write().unwrap() = Arc::new(content);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductionFormula {
+    pub resource: ResourceType,
+    pub base: i32,
+    pub per_level: i32,
+    #[serde(default = "default_level_divisor")]
+    pub level_divisor: i32,
+}
+
+fn default_level_divisor() -> i32 {
+    1
+}
+
+impl ProductionFormula {
+    // base + per_level * level / level_divisor (целочисленно)
+    pub fn amount(&self, level: u32) -> i32 {
+        self.base + self.per_level * level as i32 / self.level_divisor
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildingDefinition {
+    pub building_type: BuildingType,
+    pub display_name: String,
+    pub description: String,
+    pub base_cost: Vec<(ResourceType, u32)>,
+    #[serde(default = "default_build_time")]
+    pub build_time: u32, // Тиков на строительство первого уровня
+    #[serde(default = "default_salvage_fraction")]
+    pub salvage_fraction: f32, // Доля вложенных ресурсов, возвращаемая при сносе
+    #[serde(default)]
+    pub maintenance: Vec<(ResourceType, u32)>, // Содержание за тик на уровень здания
+    #[serde(default = "default_decay_rate")]
+    pub decay_rate: f32, // Потеря состояния за тик
+    #[serde(default)]
+    pub production: Vec<ProductionFormula>,
+    #[serde(default)]
+    pub workers: Vec<WorkerSlot>,
+    #[serde(default)]
+    pub storage: Vec<(ResourceType, u32)>, // Вместимость хранилищ на уровень здания
+    #[serde(default)]
+    pub upgrade_goods: Vec<(GoodType, u32)>, // Товары для улучшений высоких уровней
+    #[serde(default)]
+    pub deposit_bonus: f32, // Прибавка к добыче рядом с месторождением (0.5 = +50%)
+}
+
+fn default_build_time() -> u32 {
+    5
+}
+
+fn default_salvage_fraction() -> f32 {
+    0.5
+}
+
+fn default_decay_rate() -> f32 {
+    0.002
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerrainDefinition {
+    pub terrain: Terrain,
+    pub display_name: String,
+    #[serde(default)]
+    pub resource_modifiers: Vec<(ResourceType, f32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContentFormat {
+    Ron,
+    Toml,
+}
+
+impl ContentFormat {
+    pub fn from_path(path: &Path) -> Option<ContentFormat> {
+        match path.extension()?.to_str()? {
+            "ron" => Some(ContentFormat::Ron),
+            "toml" => Some(ContentFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+// Один файл определений. Все разделы необязательны
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentFile {
+    pub buildings: Vec<BuildingDefinition>,
+    pub terrains: Vec<TerrainDefinition>,
+    pub market_prices: Vec<(ResourceType, u32)>,
+    pub adjacency: Vec<AdjacencyRule>,
+    pub goods: Vec<GoodDefinition>,
+    pub recipes: Vec<Recipe>,
+    pub technologies: Vec<Technology>,
+}
+
+impl ContentFile {
+    pub fn parse(source: &str, format: ContentFormat) -> Result<ContentFile, String> {
+        match format {
+            ContentFormat::Ron => ron::from_str(source).map_err(|e| e.to_string()),
+            ContentFormat::Toml => toml::from_str(source).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<ContentFile, String> {
+        let format = ContentFormat::from_path(path)
+            .ok_or_else(|| format!("{}: неизвестный формат файла", path.display()))?;
+        let source =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        ContentFile::parse(&source, format).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GameContent {
+    pub buildings: HashMap<BuildingType, BuildingDefinition>,
+    pub terrains: HashMap<Terrain, TerrainDefinition>,
+    pub market_prices: HashMap<ResourceType, u32>,
+    pub adjacency: Vec<AdjacencyRule>,
+    pub goods: HashMap<GoodType, GoodDefinition>,
+    pub recipes: HashMap<GoodType, Recipe>,
+    pub technologies: TechnologyTree,
+}
+
+impl GameContent {
+    // Встроенный контент. Ошибка в нем - ошибка сборки, поэтому паникуем
+    pub fn base() -> GameContent {
+        let file = ContentFile::parse(BASE_CONTENT, ContentFormat::Ron)
+            .unwrap_or_else(|e| panic!("Встроенный контент поврежден: {}", e));
+        let mut content = GameContent::default();
+        content
+            .merge(file)
+            .and_then(|_| content.validate())
+            .unwrap_or_else(|e| panic!("Встроенный контент поврежден: {}", e));
+        content
+    }
+
+    // Загружает базовый контент и накладывает поверх него все файлы
+    // *.ron и *.toml из каталога в алфавитном порядке
+    pub fn load_dir(dir: &Path) -> Result<GameContent, String> {
+        let mut content = GameContent::base();
+
+        for path in content_files(dir)? {
+            content
+                .merge(ContentFile::load(&path)?)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+
+        content.validate()?;
+        Ok(content)
+    }
+
+    // Добавляет определения из файла, заменяя существующие с тем же ключом
+    pub fn merge(&mut self, file: ContentFile) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        for definition in file.buildings {
+            if !seen.insert(definition.building_type.clone()) {
+                return Err(format!(
+                    "Здание {:?} определено дважды",
+                    definition.building_type
+                ));
+            }
+            self.buildings
+                .insert(definition.building_type.clone(), definition);
+        }
+
+        let mut seen = HashSet::new();
+        for definition in file.terrains {
+            if !seen.insert(definition.terrain.clone()) {
+                return Err(format!(
+                    "Местность {:?} определена дважды",
+                    definition.terrain
+                ));
+            }
+            self.terrains.insert(definition.terrain.clone(), definition);
+        }
+
+        let mut seen = HashSet::new();
+        for (resource, price) in file.market_prices {
+            if !seen.insert(resource.clone()) {
+                return Err(format!("Цена ресурса {:?} указана дважды", resource));
+            }
+            self.market_prices.insert(resource, price);
+        }
+
+        // Правила для пары зданий из файла заменяют все прежние правила этой пары
+        let pairs: HashSet<(BuildingType, BuildingType)> = file
+            .adjacency
+            .iter()
+            .map(|rule| (rule.building.clone(), rule.neighbor.clone()))
+            .collect();
+        self.adjacency
+            .retain(|rule| !pairs.contains(&(rule.building.clone(), rule.neighbor.clone())));
+        self.adjacency.extend(file.adjacency);
+
+        let mut seen = HashSet::new();
+        for definition in file.goods {
+            if !seen.insert(definition.good.clone()) {
+                return Err(format!("Товар {:?} определен дважды", definition.good));
+            }
+            self.goods.insert(definition.good.clone(), definition);
+        }
+
+        let mut seen = HashSet::new();
+        for recipe in file.recipes {
+            if !seen.insert(recipe.output.clone()) {
+                return Err(format!("Рецепт {:?} определен дважды", recipe.output));
+            }
+            self.recipes.insert(recipe.output.clone(), recipe);
+        }
+
+        let mut seen = HashSet::new();
+        for technology in file.technologies {
+            if !seen.insert(technology.tech_type.clone()) {
+                return Err(format!(
+                    "Технология {:?} определена дважды",
+                    technology.tech_type
+                ));
+            }
+            self.technologies.insert(technology);
+        }
+
+        Ok(())
+    }
+
+    // Проверка схемы: полнота определений и допустимые значения
+    pub fn validate(&self) -> Result<(), String> {
+        for building_type in BuildingType::builtin() {
+            if !self.buildings.contains_key(&building_type) {
+                return Err(format!("Нет определения здания {:?}", building_type));
+            }
+        }
+
+        for definition in self.buildings.values() {
+            if definition.build_time == 0 {
+                return Err(format!(
+                    "Здание {:?}: build_time должен быть положительным",
+                    definition.building_type
+                ));
+            }
+            if !(0.0..=1.0).contains(&definition.decay_rate) {
+                return Err(format!(
+                    "Здание {:?}: decay_rate должен быть от 0 до 1",
+                    definition.building_type
+                ));
+            }
+            if !(0.0..=1.0).contains(&definition.salvage_fraction) {
+                return Err(format!(
+                    "Здание {:?}: salvage_fraction должен быть от 0 до 1",
+                    definition.building_type
+                ));
+            }
+            if !definition.deposit_bonus.is_finite() || definition.deposit_bonus < 0.0 {
+                return Err(format!(
+                    "Здание {:?}: deposit_bonus не может быть отрицательным",
+                    definition.building_type
+                ));
+            }
+            for formula in &definition.production {
+                if formula.level_divisor <= 0 {
+                    return Err(format!(
+                        "Здание {:?}: level_divisor должен быть положительным",
+                        definition.building_type
+                    ));
+                }
+            }
+        }
+
+        for terrain in Terrain::all() {
+            if !self.terrains.contains_key(&terrain) {
+                return Err(format!("Нет определения местности {:?}", terrain));
+            }
+        }
+
+        for definition in self.terrains.values() {
+            for (resource, modifier) in &definition.resource_modifiers {
+                if !modifier.is_finite() || *modifier < 0.0 {
+                    return Err(format!(
+                        "Местность {:?}: недопустимый модификатор {} для {:?}",
+                        definition.terrain, modifier, resource
+                    ));
+                }
+            }
+        }
+
+        for (resource, price) in &self.market_prices {
+            if *price == 0 {
+                return Err(format!(
+                    "Рыночная цена {:?} должна быть положительной",
+                    resource
+                ));
+            }
+        }
+
+        for rule in &self.adjacency {
+            for building_type in [&rule.building, &rule.neighbor] {
+                if !self.buildings.contains_key(building_type) {
+                    return Err(format!(
+                        "Правило соседства ссылается на неизвестное здание {:?}",
+                        building_type
+                    ));
+                }
+            }
+            let valid = match &rule.effect {
+                AdjacencyEffect::Production { bonus, .. } => bonus.is_finite() && *bonus > -1.0,
+                AdjacencyEffect::Happiness(amount) => amount.is_finite(),
+            };
+            if !valid {
+                return Err(format!(
+                    "Правило соседства {:?} - {:?}: недопустимый эффект {:?}",
+                    rule.building, rule.neighbor, rule.effect
+                ));
+            }
+        }
+
+        for good in GoodType::all() {
+            match self.goods.get(&good) {
+                None => return Err(format!("Нет определения товара {:?}", good)),
+                Some(definition) if definition.base_price == 0 => {
+                    return Err(format!("Цена товара {:?} должна быть положительной", good));
+                }
+                Some(_) => {}
+            }
+            match self.recipes.get(&good) {
+                None => return Err(format!("Нет рецепта товара {:?}", good)),
+                Some(recipe) if recipe.quantity == 0 || recipe.duration == 0 => {
+                    return Err(format!(
+                        "Рецепт {:?}: выход и длительность должны быть положительными",
+                        good
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+
+        self.technologies.validate()?;
+
+        for technology in self.technologies.get_all_technologies().values() {
+            for effect in &technology.unlock_effects {
+                let valid = match effect {
+                    TechEffect::ProductionMultiplier { multiplier, .. } => {
+                        multiplier.is_finite() && *multiplier >= 0.0
+                    }
+                    TechEffect::ConstructionDiscount(discount) => (0.0..1.0).contains(discount),
+                    TechEffect::UnlockBuilding(building_type) => {
+                        self.buildings.contains_key(building_type)
+                    }
+                    TechEffect::UnlockTradeRoutes | TechEffect::StatBonus { .. } => true,
+                };
+                if !valid {
+                    return Err(format!(
+                        "Технология {:?}: недопустимый эффект {:?}",
+                        technology.tech_type, effect
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn building(&self, building_type: &BuildingType) -> Option<&BuildingDefinition> {
+        self.buildings.get(building_type)
+    }
+
+    pub fn terrain(&self, terrain: &Terrain) -> Option<&TerrainDefinition> {
+        self.terrains.get(terrain)
+    }
+}
+
+// Следит за каталогом контента и перезагружает его при изменении файлов
+pub struct ContentWatcher {
+    dir: PathBuf,
+    fingerprint: Option<u64>,
+}
+
+impl ContentWatcher {
+    pub fn new(dir: impl Into<PathBuf>) -> ContentWatcher {
+        ContentWatcher {
+            dir: dir.into(),
+            fingerprint: None,
+        }
+    }
+
+    // Возвращает Ok(true), если был загружен и установлен новый контент.
+    // При ошибке активным остается предыдущий контент
+    pub fn poll(&mut self) -> Result<bool, String> {
+        let fingerprint = fingerprint(&self.dir)?;
+        if self.fingerprint == Some(fingerprint) {
+            return Ok(false);
+        }
+
+        // Запоминаем отпечаток заранее, чтобы не перечитывать сломанный файл каждый опрос
+        self.fingerprint = Some(fingerprint);
+        install(GameContent::load_dir(&self.dir)?);
+        Ok(true)
+    }
+}
+
+fn content_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && ContentFormat::from_path(path).is_some())
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+// Отпечаток каталога по именам и содержимому файлов контента. Время
+// изменения не подходит: две правки за одну секунду его не меняют
+fn fingerprint(dir: &Path) -> Result<u64, String> {
+    let mut hasher = DefaultHasher::new();
+    for path in content_files(dir)? {
+        let source = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        path.hash(&mut hasher);
+        source.hash(&mut hasher);
+    }
+
+    Ok(hasher.finish())
+}
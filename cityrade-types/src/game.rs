@@ -0,0 +1,196 @@
+use crate::building::BuildingType;
+use crate::caravan::CaravanEvent;
+use crate::city::City;
+use crate::content;
+use crate::market::TradeManager;
+use crate::resources::ResourceType;
+use crate::technology::{ResearchEvent, TechnologyType};
+use crate::world::{WorldGenerator, WorldMap};
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+// Сторона карты мира новой игры в клетках
+pub const WORLD_SIZE: u64 = 256;
+
+// Команды игроков. Каждая выполняется от имени конкретного игрока
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Command {
+    EstablishTradeRoute {
+        source_city: String,
+        target_city: String,
+        resource: ResourceType,
+        quantity: u32,
+    },
+    AcceptTradeRoute {
+        route_id: String,
+    },
+    RejectTradeRoute {
+        route_id: String,
+    },
+    SetRouteEscort {
+        route_id: String,
+        escort: u32,
+    },
+    StartResearch {
+        city: String,
+        technology: TechnologyType,
+    },
+    Build {
+        city: String,
+        building_type: BuildingType,
+        name: String,
+        position: (i32, i32),
+    },
+    CancelConstruction {
+        city: String,
+        project_id: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    Caravan(CaravanEvent),
+    Research { city: String, event: ResearchEvent },
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameEvent::Caravan(event) => event.fmt(f),
+            GameEvent::Research {
+                city,
+                event: ResearchEvent::Completed(tech_type),
+            } => {
+                let name = content::current()
+                    .technologies
+                    .get_technology(tech_type)
+                    .map(|tech| tech.name.clone())
+                    .unwrap_or_else(|| format!("{:?}", tech_type));
+                write!(f, "Город {} изучил технологию \"{}\"", city, name)
+            }
+        }
+    }
+}
+
+// Общий мир: города всех игроков, торговля между ними и карта
+pub struct Game {
+    pub cities: HashMap<String, City>, // ключ - имя города
+    pub trade: TradeManager,
+    pub world: WorldMap,
+    pub tick: u64,
+    rng: StdRng,
+}
+
+impl Game {
+    // Мир генерируется из того же зерна, что и случайные события игры
+    pub fn new(seed: u64) -> Game {
+        Game {
+            cities: HashMap::new(),
+            trade: TradeManager::new(),
+            world: WorldGenerator::new(Some(seed)).generate(WORLD_SIZE, WORLD_SIZE),
+            tick: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // Добавляет город в мир, занимает месторождения вокруг него и открывает
+    // в нем рынок
+    pub fn add_city(&mut self, mut city: City) -> Result<(), String> {
+        if self.cities.contains_key(&city.name) {
+            return Err(format!("Город {} уже существует", city.name));
+        }
+
+        self.trade.create_city_market(&city.name);
+        self.world
+            .add_city(city.position.0, city.position.1, city.name.clone());
+        city.claim_deposits(&mut self.world);
+        self.cities.insert(city.name.clone(), city);
+        Ok(())
+    }
+
+    // Выполняет команду игрока. Возвращает идентификатор объекта, к которому
+    // относится команда
+    pub fn execute(&mut self, player_id: &str, command: Command) -> Result<String, String> {
+        match command {
+            Command::EstablishTradeRoute {
+                source_city,
+                target_city,
+                resource,
+                quantity,
+            } => self.trade.establish_trade_route(
+                &self.cities,
+                player_id,
+                &source_city,
+                &target_city,
+                resource,
+                quantity,
+            ),
+            Command::AcceptTradeRoute { route_id } => self
+                .trade
+                .accept_trade_route(&self.cities, player_id, &route_id)
+                .map(|_| route_id),
+            Command::RejectTradeRoute { route_id } => self
+                .trade
+                .reject_trade_route(&self.cities, player_id, &route_id)
+                .map(|_| route_id),
+            Command::SetRouteEscort { route_id, escort } => self
+                .trade
+                .set_route_escort(&self.cities, player_id, &route_id, escort)
+                .map(|_| route_id),
+            Command::StartResearch { city, technology } => self
+                .owned_city(player_id, &city)?
+                .start_research(technology)
+                .map(|_| city),
+            Command::Build {
+                city,
+                building_type,
+                name,
+                position,
+            } => self
+                .owned_city(player_id, &city)?
+                .add_building(building_type, name, position),
+            Command::CancelConstruction { city, project_id } => self
+                .owned_city(player_id, &city)?
+                .cancel_construction(&project_id)
+                .map(|_| project_id),
+        }
+    }
+
+    // Город, которым распоряжается игрок
+    fn owned_city(&mut self, player_id: &str, name: &str) -> Result<&mut City, String> {
+        match self.cities.get_mut(name) {
+            Some(city) if city.owner_id == player_id => Ok(city),
+            Some(_) => Err(format!("Город {} вам не принадлежит", name)),
+            None => Err(format!("Город {} не найден", name)),
+        }
+    }
+
+    // Игровой тик: сначала обновляются города, затем идут караваны
+    pub fn tick(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+
+        let mut names: Vec<String> = self.cities.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            if let Some(city) = self.cities.get_mut(&name) {
+                events.extend(city.update().into_iter().map(|event| GameEvent::Research {
+                    city: name.clone(),
+                    event,
+                }));
+            }
+        }
+
+        events.extend(
+            self.trade
+                .update_trade_routes(&mut self.cities, &self.world, &mut self.rng)
+                .into_iter()
+                .map(GameEvent::Caravan),
+        );
+        self.trade.clear_finished_routes();
+        self.tick += 1;
+
+        events
+    }
+}
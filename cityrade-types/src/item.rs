@@ -1,5 +1,165 @@
+use crate::content;
+use crate::resources::ResourceType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
 pub trait Item {
     fn name(&self) -> String;
     fn description(&self) -> String;
     fn price(&self) -> u32;
 }
+
+// Товары, которые изготавливаются в мастерских из сырья
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GoodType {
+    Planks, // Доски
+    Tools,  // Инструменты
+    Bricks, // Кирпичи
+    Bread,  // Хлеб
+}
+
+impl GoodType {
+    pub fn all() -> [GoodType; 4] {
+        [
+            GoodType::Planks,
+            GoodType::Tools,
+            GoodType::Bricks,
+            GoodType::Bread,
+        ]
+    }
+
+    pub fn recipe(&self) -> Option<Recipe> {
+        content::current().recipes.get(self).cloned()
+    }
+}
+
+impl Item for GoodType {
+    fn name(&self) -> String {
+        match content::current().goods.get(self) {
+            Some(definition) => definition.display_name.clone(),
+            None => format!("{:?}", self),
+        }
+    }
+
+    fn description(&self) -> String {
+        content::current()
+            .goods
+            .get(self)
+            .map(|definition| definition.description.clone())
+            .unwrap_or_default()
+    }
+
+    fn price(&self) -> u32 {
+        content::current()
+            .goods
+            .get(self)
+            .map(|definition| definition.base_price)
+            .unwrap_or(0)
+    }
+}
+
+impl FromStr for GoodType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase();
+        GoodType::all()
+            .into_iter()
+            .find(|good| format!("{:?}", good).to_lowercase() == normalized)
+            .ok_or_else(|| format!("Неизвестный товар: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoodDefinition {
+    pub good: GoodType,
+    pub display_name: String,
+    pub description: String,
+    pub base_price: u32,
+}
+
+// Рецепт мастерской: сырье на одну партию и ее выход
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recipe {
+    pub output: GoodType,
+    pub quantity: u32,
+    pub inputs: Vec<(ResourceType, u32)>,
+    pub duration: u32, // в игровых тиках
+}
+
+impl Recipe {
+    pub fn inputs_for(&self, batches: u32) -> Vec<(ResourceType, u32)> {
+        self.inputs
+            .iter()
+            .map(|(resource, amount)| (resource.clone(), amount.saturating_mul(batches)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CraftingJob {
+    pub id: String,
+    pub good: GoodType,
+    pub quantity: u32, // Сколько товара получится
+    pub total_ticks: u32,
+    pub progress: u32,
+}
+
+impl CraftingJob {
+    pub fn new(recipe: &Recipe, batches: u32) -> CraftingJob {
+        CraftingJob {
+            id: Uuid::new_v4().to_string(),
+            good: recipe.output.clone(),
+            quantity: recipe.quantity.saturating_mul(batches),
+            total_ticks: recipe.duration.saturating_mul(batches),
+            progress: 0,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.progress >= self.total_ticks
+    }
+}
+
+// Склад товаров города
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    pub goods: HashMap<GoodType, u32>,
+}
+
+impl Inventory {
+    pub fn get(&self, good: &GoodType) -> u32 {
+        *self.goods.get(good).unwrap_or(&0)
+    }
+
+    pub fn add(&mut self, good: &GoodType, amount: u32) {
+        let current = self.goods.entry(good.clone()).or_insert(0);
+        *current = current.saturating_add(amount);
+    }
+
+    pub fn subtract(&mut self, good: &GoodType, amount: u32) -> bool {
+        match self.get(good).checked_sub(amount) {
+            Some(remaining) => {
+                self.goods.insert(good.clone(), remaining);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_afford(&self, costs: &[(GoodType, u32)]) -> bool {
+        costs.iter().all(|(good, amount)| self.get(good) >= *amount)
+    }
+
+    pub fn pay(&mut self, costs: &[(GoodType, u32)]) -> bool {
+        if !self.can_afford(costs) {
+            return false;
+        }
+        for (good, amount) in costs {
+            self.subtract(good, *amount);
+        }
+        true
+    }
+}
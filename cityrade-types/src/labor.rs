@@ -0,0 +1,100 @@
+use crate::building::Building;
+use crate::population::{Population, PopulationClass};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Приоритет укомплектования зданий по умолчанию
+pub const DEFAULT_PRIORITY: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerSlot {
+    pub class: PopulationClass,
+    pub per_level: u32, // Рабочих мест этого класса на уровень здания
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Staffing {
+    // Сколько жителей каждого класса работает в здании
+    pub assigned: HashMap<String, Vec<(PopulationClass, u32)>>,
+    // Доля занятых рабочих мест здания от 0.0 до 1.0
+    pub ratios: HashMap<String, f32>,
+    pub unemployed: HashMap<PopulationClass, u32>,
+}
+
+impl Staffing {
+    // Здания без рабочих мест работают всегда в полную силу
+    pub fn ratio(&self, building_id: &str) -> f32 {
+        *self.ratios.get(building_id).unwrap_or(&1.0)
+    }
+
+    // Доля безработных среди трудоспособных классов
+    pub fn unemployment_rate(&self, population: &Population) -> f32 {
+        let workforce: u32 = PopulationClass::labor_classes()
+            .iter()
+            .map(|class| population.count(class))
+            .sum();
+        if workforce == 0 {
+            return 0.0;
+        }
+
+        let unemployed: u32 = PopulationClass::labor_classes()
+            .iter()
+            .map(|class| *self.unemployed.get(class).unwrap_or(&0))
+            .sum();
+        unemployed as f32 / workforce as f32
+    }
+}
+
+// Распределяет жителей по рабочим местам. Здания с большим приоритетом
+// укомплектовываются первыми, при равенстве - в порядке идентификаторов
+pub fn assign_workers(
+    population: &Population,
+    buildings: &HashMap<String, Building>,
+    priorities: &HashMap<String, u32>,
+) -> Staffing {
+    let mut available = population.classes.clone();
+    let mut staffing = Staffing::default();
+
+    let mut order: Vec<&Building> = buildings.values().collect();
+    order.sort_by(|a, b| {
+        let priority_a = priorities.get(&a.id).unwrap_or(&DEFAULT_PRIORITY);
+        let priority_b = priorities.get(&b.id).unwrap_or(&DEFAULT_PRIORITY);
+        priority_b.cmp(priority_a).then_with(|| a.id.cmp(&b.id))
+    });
+
+    for building in order {
+        let slots = building.worker_slots();
+        if slots.is_empty() {
+            continue;
+        }
+
+        let mut total = 0;
+        let mut filled = 0;
+        let mut assigned = Vec::new();
+
+        for (class, needed) in slots {
+            let free = available.entry(class.clone()).or_insert(0);
+            let hired = needed.min(*free);
+            *free -= hired;
+
+            total += needed;
+            filled += hired;
+            assigned.push((class, hired));
+        }
+
+        let ratio = if total == 0 {
+            1.0
+        } else {
+            filled as f32 / total as f32
+        };
+        staffing.ratios.insert(building.id.clone(), ratio);
+        staffing.assigned.insert(building.id.clone(), assigned);
+    }
+
+    staffing.unemployed = available
+        .into_iter()
+        .filter(|(class, count)| *count > 0 && PopulationClass::labor_classes().contains(class))
+        .collect();
+
+    staffing
+}
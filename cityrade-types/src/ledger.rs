@@ -0,0 +1,150 @@
+use crate::resources::ResourceType;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+// Сколько последних записей хранит журнал города
+pub const LEDGER_CAPACITY: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LedgerReason {
+    Production,
+    Consumption,
+    Construction,
+    Refund,
+    Salvage,
+    Maintenance,
+    Repair,
+    Training,
+    Crafting,
+    Trade,
+    Spoilage,
+    Adjustment,
+}
+
+impl fmt::Display for LedgerReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LedgerReason::Production => write!(f, "Производство"),
+            LedgerReason::Consumption => write!(f, "Потребление"),
+            LedgerReason::Construction => write!(f, "Строительство"),
+            LedgerReason::Refund => write!(f, "Возврат"),
+            LedgerReason::Salvage => write!(f, "Снос"),
+            LedgerReason::Maintenance => write!(f, "Обслуживание"),
+            LedgerReason::Repair => write!(f, "Ремонт"),
+            LedgerReason::Training => write!(f, "Обучение"),
+            LedgerReason::Crafting => write!(f, "Мастерские"),
+            LedgerReason::Trade => write!(f, "Торговля"),
+            LedgerReason::Spoilage => write!(f, "Порча"),
+            LedgerReason::Adjustment => write!(f, "Прочее"),
+        }
+    }
+}
+
+// Набор изменений ресурсов, который применяется целиком или не применяется вовсе
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub reason: LedgerReason,
+    pub deltas: Vec<(ResourceType, i64)>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl LedgerEntry {
+    pub fn new(reason: LedgerReason) -> LedgerEntry {
+        LedgerEntry {
+            reason,
+            deltas: Vec::new(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    pub fn credit(mut self, resource: ResourceType, amount: u32) -> LedgerEntry {
+        self.deltas.push((resource, amount as i64));
+        self
+    }
+
+    pub fn debit(mut self, resource: ResourceType, amount: u32) -> LedgerEntry {
+        self.deltas.push((resource, -(amount as i64)));
+        self
+    }
+
+    // Запись, списывающая стоимость
+    pub fn cost(reason: LedgerReason, costs: &[(ResourceType, u32)]) -> LedgerEntry {
+        costs
+            .iter()
+            .fold(LedgerEntry::new(reason), |entry, (resource, amount)| {
+                entry.debit(resource.clone(), *amount)
+            })
+    }
+
+    // Запись, зачисляющая ресурсы
+    pub fn income(reason: LedgerReason, amounts: &[(ResourceType, u32)]) -> LedgerEntry {
+        amounts
+            .iter()
+            .fold(LedgerEntry::new(reason), |entry, (resource, amount)| {
+                entry.credit(resource.clone(), *amount)
+            })
+    }
+
+    // Итоговое изменение каждого ресурса
+    pub fn net(&self) -> HashMap<ResourceType, i64> {
+        let mut net = HashMap::new();
+        for (resource, delta) in &self.deltas {
+            *net.entry(resource.clone()).or_insert(0) += delta;
+        }
+        net
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deltas.iter().all(|(_, delta)| *delta == 0)
+    }
+}
+
+impl fmt::Display for LedgerEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {}:",
+            self.timestamp.format("%H:%M:%S"),
+            self.reason
+        )?;
+        for (resource, delta) in &self.deltas {
+            write!(f, " {} {:+}", resource, delta)?;
+        }
+        Ok(())
+    }
+}
+
+// Журнал последних операций с ресурсами
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ledger {
+    pub entries: VecDeque<LedgerEntry>,
+    pub capacity: usize,
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Ledger {
+            entries: VecDeque::new(),
+            capacity: LEDGER_CAPACITY,
+        }
+    }
+}
+
+impl Ledger {
+    pub fn record(&mut self, entry: LedgerEntry) {
+        if entry.is_empty() {
+            return;
+        }
+        self.entries.push_back(entry);
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    // Последние `count` записей, начиная с самой новой
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = &LedgerEntry> {
+        self.entries.iter().rev().take(count)
+    }
+}
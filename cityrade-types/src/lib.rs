@@ -1,13 +1,27 @@
 pub mod account;
+pub mod adjacency;
+pub mod budget;
 pub mod building;
+pub mod caravan;
 pub mod chat;
+pub mod chunk;
 pub mod city;
+pub mod construction;
+pub mod content;
+pub mod game;
 pub mod generator;
 pub mod item;
+pub mod labor;
+pub mod ledger;
 pub mod market;
+pub mod mobility;
+pub mod needs;
 pub mod plugin;
 pub mod population;
+pub mod power;
+pub mod production;
 pub mod resources;
+pub mod storage;
 pub mod technology;
 pub mod world;
 
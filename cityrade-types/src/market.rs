@@ -1,7 +1,16 @@
+use crate::caravan::{Caravan, CaravanEvent, CaravanStatus};
 use crate::city::City;
+use crate::content;
+use crate::item::{GoodType, Item};
+use crate::ledger::LedgerReason;
+use crate::population::PopulationClass;
 use crate::resources::{ResourceType, Resources};
+use crate::technology::TechEffect;
+use crate::world::WorldMap;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketItem {
@@ -11,11 +20,22 @@ pub struct MarketItem {
     pub current_price: u32,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GoodListing {
+    pub good: GoodType,
+    pub quantity: u32,
+    pub base_price: u32,
+    pub current_price: u32,
+    pub demand_factor: f32,
+    pub supply_factor: f32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Market {
     pub items: HashMap<ResourceType, MarketItem>,
     pub demand_factors: HashMap<ResourceType, f32>,
     pub supply_factors: HashMap<ResourceType, f32>,
+    pub goods: HashMap<GoodType, GoodListing>,
 }
 
 impl Market {
@@ -24,18 +44,7 @@ impl Market {
         let mut demand_factors = HashMap::new();
         let mut supply_factors = HashMap::new();
 
-        for resource in [ResourceType::Gold, ResourceType::Wood, ResourceType::Stone].iter() {
-            let base_price = match *resource {
-                ResourceType::Gold => 100,
-                ResourceType::Wood => 20,
-                ResourceType::Stone => 40,
-                ResourceType::Food => todo!(),
-                ResourceType::Iron => todo!(),
-                ResourceType::Crystal => todo!(),
-                ResourceType::Population => todo!(),
-                ResourceType::Energy => todo!(),
-            };
-
+        for (resource, &base_price) in content::current().market_prices.iter() {
             items.insert(resource.clone(), MarketItem {
                 resource: resource.clone(),
                 quantity: 100,
@@ -47,10 +56,27 @@ impl Market {
             supply_factors.insert(resource.clone(), 1.0);
         }
 
+        let goods = GoodType::all()
+            .into_iter()
+            .map(|good| {
+                let base_price = good.price();
+                let listing = GoodListing {
+                    good: good.clone(),
+                    quantity: 50,
+                    base_price,
+                    current_price: base_price,
+                    demand_factor: 1.0,
+                    supply_factor: 1.0,
+                };
+                (good, listing)
+            })
+            .collect();
+
         Market {
             items,
             demand_factors,
             supply_factors,
+            goods,
         }
     }
 
@@ -62,6 +88,39 @@ impl Market {
             let market_factor = demand / supply;
             item.current_price = ((item.base_price as f32) * market_factor) as u32;
         }
+
+        for listing in self.goods.values_mut() {
+            let market_factor = listing.demand_factor / listing.supply_factor;
+            listing.current_price = ((listing.base_price as f32) * market_factor) as u32;
+        }
+    }
+
+    // Покупка товара мастерских. Возвращает стоимость покупки
+    pub fn buy_good(&mut self, good: &GoodType, quantity: u32) -> Result<u32, String> {
+        let listing = self
+            .goods
+            .get_mut(good)
+            .ok_or_else(|| format!("Товар {} не найден на рынке", good.name()))?;
+
+        if listing.quantity < quantity {
+            return Err(format!("Недостаточно товара {} на рынке", good.name()));
+        }
+
+        listing.quantity -= quantity;
+        listing.demand_factor += 0.01; // Увеличиваем спрос
+        Ok(listing.current_price.saturating_mul(quantity))
+    }
+
+    // Продажа товара мастерских. Возвращает выручку
+    pub fn sell_good(&mut self, good: &GoodType, quantity: u32) -> Result<u32, String> {
+        let listing = self
+            .goods
+            .get_mut(good)
+            .ok_or_else(|| format!("Товар {} не найден на рынке", good.name()))?;
+
+        listing.quantity = listing.quantity.saturating_add(quantity);
+        listing.supply_factor += 0.01; // Увеличиваем предложение
+        Ok(listing.current_price.saturating_mul(quantity))
     }
 
     pub fn buy(&mut self, resource: &ResourceType, quantity: u32) -> Result<u32, String> {
@@ -77,17 +136,11 @@ impl Market {
 
                 return Ok(price);
             } else {
-                return Err(format!(
-                    "Недостаточно ресурса {} на рынке",
-                    resource_to_string(resource)
-                ));
+                return Err(format!("Недостаточно ресурса {} на рынке", resource));
             }
         }
 
-        Err(format!(
-            "Ресурс {} не найден на рынке",
-            resource_to_string(resource)
-        ))
+        Err(format!("Ресурс {} не найден на рынке", resource))
     }
 
     pub fn sell(&mut self, resource: &ResourceType, quantity: u32) -> Result<u32, String> {
@@ -103,39 +156,49 @@ impl Market {
             return Ok(revenue);
         }
 
-        Err(format!(
-            "Ресурс {} не найден на рынке",
-            resource_to_string(resource)
-        ))
+        Err(format!("Ресурс {} не найден на рынке", resource))
     }
 }
 
-// Вспомогательная функция для преобразования типа ресурса в строку
-fn resource_to_string(resource: &ResourceType) -> &str {
-    match resource {
-        ResourceType::Gold => "Золото",
-        ResourceType::Wood => "Древесина",
-        ResourceType::Stone => "Камень",
-        ResourceType::Food => todo!(),
-        ResourceType::Iron => todo!(),
-        ResourceType::Crystal => todo!(),
-        ResourceType::Population => todo!(),
-        ResourceType::Energy => todo!(),
-    }
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContractStatus {
+    Proposed,  // Ожидает согласия владельца города-получателя
+    Accepted,  // Согласован, караван будет отправлен
+    Rejected,  // Отклонен получателем
+    Completed, // Груз доставлен и оплачен
+    Failed,    // Сделка сорвалась: не хватило ресурсов или караван потерян
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TradeRoute {
+    pub id: String,
     pub source_city: String,
     pub target_city: String,
     pub resource: ResourceType,
     pub quantity: u32,
     pub price_per_unit: u32,
     pub duration: u32, // в игровых тиках
+    pub escort: u32,   // Солдаты сопровождения каравана
+    pub status: ContractStatus,
+    pub caravan_id: Option<String>,
+}
+
+impl TradeRoute {
+    pub fn total_price(&self) -> u32 {
+        self.quantity.saturating_mul(self.price_per_unit)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            ContractStatus::Rejected | ContractStatus::Completed | ContractStatus::Failed
+        )
+    }
 }
 
 pub struct TradeManager {
     pub routes: Vec<TradeRoute>,
+    pub caravans: Vec<Caravan>,
     pub markets: HashMap<String, Market>, // ключ - имя города
 }
 
@@ -143,6 +206,7 @@ impl TradeManager {
     pub fn new() -> TradeManager {
         TradeManager {
             routes: Vec::new(),
+            caravans: Vec::new(),
             markets: HashMap::new(),
         }
     }
@@ -151,13 +215,42 @@ impl TradeManager {
         self.markets.insert(city_name.to_string(), Market::new());
     }
 
+    // Предлагает торговый контракт от имени владельца города-источника.
+    // Если оба города принадлежат одному владельцу, контракт принимается сразу
     pub fn establish_trade_route(
         &mut self,
+        cities: &HashMap<String, City>,
+        owner_id: &str,
         source_city: &str,
         target_city: &str,
         resource: ResourceType,
         quantity: u32,
-    ) -> Result<(), String> {
+    ) -> Result<String, String> {
+        if source_city == target_city {
+            return Err("Нельзя торговать с самим собой".to_string());
+        }
+
+        let source = cities
+            .get(source_city)
+            .ok_or_else(|| format!("Город {} не найден", source_city))?;
+        let target = cities
+            .get(target_city)
+            .ok_or_else(|| format!("Город {} не найден", target_city))?;
+
+        if source.owner_id != owner_id {
+            return Err(format!("Город {} вам не принадлежит", source_city));
+        }
+
+        let can_trade = source
+            .technology_effects()
+            .contains(&TechEffect::UnlockTradeRoutes);
+        if !can_trade {
+            return Err(format!(
+                "Город {} еще не изучил технологию торговых маршрутов",
+                source_city
+            ));
+        }
+
         // Проверяем, существуют ли города и их рынки
         if !self.markets.contains_key(source_city) {
             return Err(format!("Город-источник {} не имеет рынка", source_city));
@@ -174,101 +267,283 @@ impl TradeManager {
         } else {
             return Err(format!(
                 "Ресурс {} не найден на рынке города {}",
-                resource_to_string(&resource),
-                source_city
+                resource, source_city
             ));
         };
 
-        // Рассчитываем длительность маршрута (можно доработать на основе расстояния)
-        let duration = 10; // Заглушка, можно вычислять на основе расстояния между городами
+        let status = if target.owner_id == owner_id {
+            ContractStatus::Accepted
+        } else {
+            ContractStatus::Proposed
+        };
 
-        // Создаем и добавляем торговый маршрут
+        // Создаем и добавляем торговый маршрут.
+        // Длительность уточняется при отправке каравана по длине пути
+        let id = Uuid::new_v4().to_string();
         let route = TradeRoute {
+            id: id.clone(),
             source_city: source_city.to_string(),
             target_city: target_city.to_string(),
             resource,
             quantity,
             price_per_unit: price,
-            duration,
+            duration: 0,
+            escort: 0,
+            status,
+            caravan_id: None,
         };
 
         self.routes.push(route);
+        Ok(id)
+    }
+
+    // Назначает сопровождение каравану, пока он еще не отправлен. Солдат
+    // выделяет владелец города-источника из своего гарнизона
+    pub fn set_route_escort(
+        &mut self,
+        cities: &HashMap<String, City>,
+        owner_id: &str,
+        route_id: &str,
+        escort: u32,
+    ) -> Result<(), String> {
+        let route = self
+            .routes
+            .iter_mut()
+            .find(|r| r.id == route_id)
+            .ok_or_else(|| "Торговый маршрут не найден".to_string())?;
+
+        let source = cities
+            .get(&route.source_city)
+            .ok_or_else(|| format!("Город {} не найден", route.source_city))?;
+        if source.owner_id != owner_id {
+            return Err(format!("Город {} вам не принадлежит", route.source_city));
+        }
+
+        if route.caravan_id.is_some() || route.is_finished() {
+            return Err("Караван уже отправлен".to_string());
+        }
+
+        if source.population.count(&PopulationClass::Soldier) < escort {
+            return Err(format!(
+                "В городе {} недостаточно солдат для сопровождения",
+                route.source_city
+            ));
+        }
+
+        route.escort = escort;
+        Ok(())
+    }
+
+    pub fn accept_trade_route(
+        &mut self,
+        cities: &HashMap<String, City>,
+        owner_id: &str,
+        route_id: &str,
+    ) -> Result<(), String> {
+        let route = self.proposed_route_for(cities, owner_id, route_id)?;
+        route.status = ContractStatus::Accepted;
+        Ok(())
+    }
+
+    pub fn reject_trade_route(
+        &mut self,
+        cities: &HashMap<String, City>,
+        owner_id: &str,
+        route_id: &str,
+    ) -> Result<(), String> {
+        let route = self.proposed_route_for(cities, owner_id, route_id)?;
+        route.status = ContractStatus::Rejected;
         Ok(())
     }
 
-    pub fn update_trade_routes(&mut self, cities: &mut HashMap<String, City>) {
-        let mut completed_routes = Vec::new();
+    // Находит предложенный контракт, решение по которому принимает owner_id
+    fn proposed_route_for(
+        &mut self,
+        cities: &HashMap<String, City>,
+        owner_id: &str,
+        route_id: &str,
+    ) -> Result<&mut TradeRoute, String> {
+        let route = self
+            .routes
+            .iter_mut()
+            .find(|r| r.id == route_id)
+            .ok_or_else(|| "Торговый маршрут не найден".to_string())?;
+
+        if route.status != ContractStatus::Proposed {
+            return Err("Контракт уже не ожидает решения".to_string());
+        }
+
+        match cities.get(&route.target_city) {
+            Some(target) if target.owner_id == owner_id => Ok(route),
+            Some(_) => Err(format!("Город {} вам не принадлежит", route.target_city)),
+            None => Err(format!("Город {} не найден", route.target_city)),
+        }
+    }
+
+    pub fn get_route(&self, route_id: &str) -> Option<&TradeRoute> {
+        self.routes.iter().find(|r| r.id == route_id)
+    }
+
+    pub fn get_caravan(&self, caravan_id: &str) -> Option<&Caravan> {
+        self.caravans.iter().find(|c| c.id == caravan_id)
+    }
+
+    // Удаляет из списка завершенные, отклоненные и сорвавшиеся контракты
+    pub fn clear_finished_routes(&mut self) {
+        self.routes.retain(|r| !r.is_finished());
+    }
+
+    pub fn update_trade_routes(
+        &mut self,
+        cities: &mut HashMap<String, City>,
+        world: &WorldMap,
+        rng: &mut impl Rng,
+    ) -> Vec<CaravanEvent> {
+        let mut events = Vec::new();
+
+        // Отправляем караваны по согласованным контрактам
+        for route in self
+            .routes
+            .iter_mut()
+            .filter(|r| r.status == ContractStatus::Accepted && r.caravan_id.is_none())
+        {
+            let (Some(source), Some(target)) = (
+                cities.get(&route.source_city),
+                cities.get(&route.target_city),
+            ) else {
+                route.status = ContractStatus::Failed;
+                continue;
+            };
+
+            // Груз, оплата и сопровождение должны быть в наличии
+            let payment = route.total_price();
+            if source.resources.get(&route.resource) < route.quantity
+                || target.resources.get(&ResourceType::Gold) < payment
+                || source.population.count(&PopulationClass::Soldier) < route.escort
+            {
+                route.status = ContractStatus::Failed;
+                continue;
+            }
+
+            let caravan = Caravan::new(route, source, target);
 
-        for (index, route) in self.routes.iter_mut().enumerate() {
-            // Уменьшаем оставшееся время маршрута
-            if route.duration > 0 {
-                route.duration -= 1;
+            // Груз и солдаты сопровождения покидают город продавца,
+            // а оплата покупателя удерживается до прибытия каравана
+            let source = cities.get_mut(&route.source_city).unwrap();
+            if !source.subtract_resources(LedgerReason::Trade, &route.resource, route.quantity) {
+                route.status = ContractStatus::Failed;
+                continue;
             }
+            let target = cities.get_mut(&route.target_city).unwrap();
+            if !target.subtract_resources(LedgerReason::Trade, &ResourceType::Gold, payment) {
+                // Покупатель не смог заплатить: груз остается у продавца
+                cities.get_mut(&route.source_city).unwrap().add_resources(
+                    LedgerReason::Refund,
+                    &route.resource,
+                    route.quantity,
+                );
+                route.status = ContractStatus::Failed;
+                continue;
+            }
+            cities
+                .get_mut(&route.source_city)
+                .unwrap()
+                .population
+                .remove_citizens(&PopulationClass::Soldier, route.escort);
+
+            route.duration = caravan.remaining_steps() as u32;
+            route.caravan_id = Some(caravan.id.clone());
+            self.caravans.push(caravan);
+        }
+
+        // Продвигаем караваны в пути
+        for caravan in self.caravans.iter_mut() {
+            caravan.advance(world, cities, rng);
+
+            let route = self.routes.iter_mut().find(|r| r.id == caravan.route_id);
+
+            match caravan.status {
+                CaravanStatus::InTransit => {
+                    if let Some(route) = route {
+                        route.duration = caravan.remaining_steps() as u32;
+                    }
+                }
+                CaravanStatus::Arrived => {
+                    // Покупатель получает груз, продавец - оплату
+                    if let Some(target) = cities.get_mut(&caravan.target_city) {
+                        target.add_resources(
+                            LedgerReason::Trade,
+                            &caravan.resource,
+                            caravan.quantity,
+                        );
+                    }
+                    // Сопровождение возвращается домой вместе с выручкой
+                    if let Some(source) = cities.get_mut(&caravan.source_city) {
+                        source.add_resources(
+                            LedgerReason::Trade,
+                            &ResourceType::Gold,
+                            caravan.payment,
+                        );
+                        source
+                            .population
+                            .add_citizens(PopulationClass::Soldier, caravan.escort);
+                    }
+
+                    // Обновляем факторы спроса и предложения на рынках
+                    if let Some(source_market) = self.markets.get_mut(&caravan.source_city)
+                        && let Some(supply_factor) =
+                            source_market.supply_factors.get_mut(&caravan.resource)
+                    {
+                        *supply_factor -= 0.02; // Уменьшаем предложение в исходном городе
+                    }
+
+                    if let Some(target_market) = self.markets.get_mut(&caravan.target_city)
+                        && let Some(demand_factor) =
+                            target_market.demand_factors.get_mut(&caravan.resource)
+                    {
+                        *demand_factor -= 0.02; // Уменьшаем спрос в городе-получателе
+                    }
 
-            // Если маршрут завершен
-            if route.duration == 0 {
-                completed_routes.push(index);
-
-                // Передаем ресурсы между городами
-                if route.source_city == route.target_city {
-                    if let Some(city) = cities.get_mut(&route.source_city) {
-                        // Списываем ресурсы из города
-                        if city.subtract_resources(&route.resource, route.quantity) {
-                            // Добавляем ресурсы тому же городу
-                            city.add_resources(&route.resource, route.quantity);
-
-                            // Обновляем факторы спроса и предложения на рынке
-                            if let Some(market) = self.markets.get_mut(&route.source_city) {
-                                if let Some(supply_factor) = market.supply_factors.get_mut(&route.resource) {
-                                    *supply_factor -= 0.02; // Уменьшаем предложение
-                                }
-                                if let Some(demand_factor) = market.demand_factors.get_mut(&route.resource) {
-                                    *demand_factor -= 0.02; // Уменьшаем спрос
-                                }
-                            }
-                        }
+                    if let Some(route) = route {
+                        route.duration = 0;
+                        route.status = ContractStatus::Completed;
+                    }
+                    events.push(CaravanEvent::Arrived {
+                        caravan_id: caravan.id.clone(),
+                        route_id: caravan.route_id.clone(),
+                    });
+                }
+                CaravanStatus::Lost => {
+                    // Груз и сопровождение потеряны, оплата возвращается покупателю
+                    if let Some(target) = cities.get_mut(&caravan.target_city) {
+                        target.add_resources(
+                            LedgerReason::Refund,
+                            &ResourceType::Gold,
+                            caravan.payment,
+                        );
                     }
-                } else {
-                    // Безопасно получаем два раздельных изменяемых заимствования используя raw pointers
-                    let (source_city, target_city) = {
-                        let source_ptr = cities.get_mut(&route.source_city).unwrap() as *mut City;
-                        let target_ptr = cities.get_mut(&route.target_city).unwrap() as *mut City;
-                        unsafe { (&mut *source_ptr, &mut *target_ptr) }
-                    };
-                    // Списываем ресурсы из исходного города
-                    if source_city.subtract_resources(&route.resource, route.quantity) {
-                        // Добавляем ресурсы городу-получателю
-                        target_city.add_resources(&route.resource, route.quantity);
-
-                        // Обновляем факторы спроса и предложения на рынках
-                        if let Some(source_market) = self.markets.get_mut(&route.source_city) {
-                            if let Some(supply_factor) =
-                                source_market.supply_factors.get_mut(&route.resource)
-                            {
-                                *supply_factor -= 0.02; // Уменьшаем предложение в исходном городе
-                            }
-                        }
-
-                        if let Some(target_market) = self.markets.get_mut(&route.target_city) {
-                            if let Some(demand_factor) =
-                                target_market.demand_factors.get_mut(&route.resource)
-                            {
-                                *demand_factor -= 0.02; // Уменьшаем спрос в городе-получателе
-                            }
-                        }
+
+                    if let Some(route) = route {
+                        route.status = ContractStatus::Failed;
                     }
+                    events.push(CaravanEvent::Lost {
+                        caravan_id: caravan.id.clone(),
+                        route_id: caravan.route_id.clone(),
+                        position: caravan.position(),
+                    });
                 }
             }
         }
 
-        // Удаляем завершенные маршруты (в обратном порядке, чтобы индексы не сдвигались)
-        for index in completed_routes.iter().rev() {
-            self.routes.remove(*index);
-        }
+        // Удаляем караваны, завершившие путь
+        self.caravans
+            .retain(|c| c.status == CaravanStatus::InTransit);
 
         // Обновляем цены на всех рынках
         for market in self.markets.values_mut() {
             market.update_prices();
         }
+
+        events
     }
 }
@@ -0,0 +1,154 @@
+use crate::building::BuildingType;
+use crate::population::PopulationClass;
+use crate::resources::ResourceType;
+use crate::technology::TechnologyType;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Сколько крестьян за тик становятся торговцами на каждый уровень рынка
+pub const MERCHANTS_PER_MARKET_LEVEL: f32 = 0.05;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConversionRequirement {
+    None,
+    Building(BuildingType),
+    Technology(TechnologyType),
+    Culture(u32), // Минимальный уровень культуры города
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionRule {
+    pub from: PopulationClass,
+    pub to: PopulationClass,
+    pub cost: Vec<(ResourceType, u32)>, // Стоимость на одного жителя
+    pub duration: u32,                  // в игровых тиках
+    pub requirement: ConversionRequirement,
+}
+
+impl ConversionRule {
+    pub fn all() -> Vec<ConversionRule> {
+        vec![
+            // Солдат обучают в казармах
+            ConversionRule {
+                from: PopulationClass::Peasant,
+                to: PopulationClass::Soldier,
+                cost: vec![(ResourceType::Gold, 10), (ResourceType::Iron, 2)],
+                duration: 5,
+                requirement: ConversionRequirement::Building(BuildingType::Barracks),
+            },
+            ConversionRule {
+                from: PopulationClass::Worker,
+                to: PopulationClass::Soldier,
+                cost: vec![(ResourceType::Gold, 10), (ResourceType::Iron, 2)],
+                duration: 5,
+                requirement: ConversionRequirement::Building(BuildingType::Barracks),
+            },
+            // Ученых готовит система образования
+            ConversionRule {
+                from: PopulationClass::Peasant,
+                to: PopulationClass::Scholar,
+                cost: vec![(ResourceType::Gold, 25)],
+                duration: 15,
+                requirement: ConversionRequirement::Technology(TechnologyType::Education),
+            },
+            ConversionRule {
+                from: PopulationClass::Worker,
+                to: PopulationClass::Scholar,
+                cost: vec![(ResourceType::Gold, 20)],
+                duration: 10,
+                requirement: ConversionRequirement::Technology(TechnologyType::Education),
+            },
+            // Знать возвышается из торговцев в культурных городах
+            ConversionRule {
+                from: PopulationClass::Merchant,
+                to: PopulationClass::Noble,
+                cost: vec![(ResourceType::Gold, 100), (ResourceType::Crystal, 5)],
+                duration: 20,
+                requirement: ConversionRequirement::Culture(30),
+            },
+            // Понижения: демобилизация и уход из науки
+            ConversionRule {
+                from: PopulationClass::Soldier,
+                to: PopulationClass::Peasant,
+                cost: vec![],
+                duration: 1,
+                requirement: ConversionRequirement::None,
+            },
+            ConversionRule {
+                from: PopulationClass::Scholar,
+                to: PopulationClass::Worker,
+                cost: vec![],
+                duration: 1,
+                requirement: ConversionRequirement::None,
+            },
+        ]
+    }
+
+    pub fn find(from: &PopulationClass, to: &PopulationClass) -> Option<ConversionRule> {
+        ConversionRule::all()
+            .into_iter()
+            .find(|rule| &rule.from == from && &rule.to == to)
+    }
+
+    pub fn total_cost(&self, count: u32) -> Vec<(ResourceType, u32)> {
+        self.cost
+            .iter()
+            .map(|(resource, amount)| (resource.clone(), amount.saturating_mul(count)))
+            .collect()
+    }
+}
+
+// Жители, которые сейчас переходят в другой класс. Пока идет обучение,
+// они не числятся ни в одном классе и не работают
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassConversion {
+    pub id: String,
+    pub from: PopulationClass,
+    pub to: PopulationClass,
+    pub count: u32,
+    pub remaining_ticks: u32,
+}
+
+impl ClassConversion {
+    pub fn new(rule: &ConversionRule, count: u32) -> ClassConversion {
+        ClassConversion {
+            id: Uuid::new_v4().to_string(),
+            from: rule.from.clone(),
+            to: rule.to.clone(),
+            count,
+            remaining_ticks: rule.duration,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Mobility {
+    pub conversions: Vec<ClassConversion>,
+    pub merchant_progress: f32, // Накопленный прирост торговцев от рынков
+}
+
+impl Mobility {
+    // Продвигает обучение на один тик и возвращает завершенные переходы
+    pub fn tick(&mut self) -> Vec<ClassConversion> {
+        for conversion in self.conversions.iter_mut() {
+            conversion.remaining_ticks = conversion.remaining_ticks.saturating_sub(1);
+        }
+
+        let (finished, in_progress) = self
+            .conversions
+            .drain(..)
+            .partition(|c| c.remaining_ticks == 0);
+        self.conversions = in_progress;
+
+        finished
+    }
+
+    // Жители, которые сейчас проходят обучение
+    pub fn in_training(&self) -> u32 {
+        self.conversions.iter().map(|c| c.count).sum()
+    }
+
+    pub fn is_training(&self, class: &PopulationClass) -> bool {
+        self.conversions.iter().any(|c| &c.to == class)
+    }
+}
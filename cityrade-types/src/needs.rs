@@ -0,0 +1,170 @@
+use crate::ledger::{LedgerEntry, LedgerReason};
+use crate::population::{Population, PopulationClass};
+use crate::resources::{ResourceType, Resources};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+// Счастье, к которому стремится класс без внешних факторов
+pub const BASE_HAPPINESS: f32 = 0.5;
+// Какую долю разрыва до целевого счастья класс проходит за тик
+pub const HAPPINESS_ADJUSTMENT_RATE: f32 = 0.1;
+// Погрешность округления спроса, чтобы 2.0000001 не превращалось в 3
+const DEMAND_EPSILON: f32 = 0.001;
+
+// Потребительская корзина класса: сколько ресурса потребляет один житель за тик
+pub fn basket(class: &PopulationClass, food_consumption: f32) -> Vec<(ResourceType, f32)> {
+    let mut basket = vec![(ResourceType::Food, food_consumption)];
+    match class {
+        PopulationClass::Peasant => {}
+        PopulationClass::Worker => basket.push((ResourceType::Energy, 0.2)),
+        PopulationClass::Merchant => basket.push((ResourceType::Gold, 0.1)),
+        PopulationClass::Soldier => basket.push((ResourceType::Iron, 0.05)),
+        PopulationClass::Scholar => basket.push((ResourceType::Energy, 0.1)),
+        PopulationClass::Noble => {
+            basket.push((ResourceType::Gold, 0.5));
+            basket.push((ResourceType::Crystal, 0.05));
+        }
+    }
+    basket
+}
+
+// Насколько потребность класса в ресурсе важна для его счастья
+fn shortage_weight(resource: &ResourceType) -> f32 {
+    match resource {
+        ResourceType::Food => 0.4,
+        _ => 0.15,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HappinessFactor {
+    Base,
+    NeedsMet,
+    Shortage(ResourceType),
+    Temples,
+    Barracks,
+    Adjacency,
+    Unemployment,
+    Taxes,
+    Technology,
+}
+
+impl fmt::Display for HappinessFactor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HappinessFactor::Base => write!(f, "Базовый уровень"),
+            HappinessFactor::NeedsMet => write!(f, "Потребности удовлетворены"),
+            HappinessFactor::Shortage(resource) => write!(f, "Нехватка ресурса \"{}\"", resource),
+            HappinessFactor::Temples => write!(f, "Храмы"),
+            HappinessFactor::Barracks => write!(f, "Казармы"),
+            HappinessFactor::Adjacency => write!(f, "Соседство зданий"),
+            HappinessFactor::Unemployment => write!(f, "Безработица"),
+            HappinessFactor::Taxes => write!(f, "Налоги"),
+            HappinessFactor::Technology => write!(f, "Технологии"),
+        }
+    }
+}
+
+// Почему изменилось счастье класса за последний тик
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HappinessBreakdown {
+    pub factors: Vec<(HappinessFactor, f32)>,
+    pub previous: f32,
+    pub current: f32,
+}
+
+impl HappinessBreakdown {
+    // Уровень счастья, к которому стремится класс
+    pub fn target(&self) -> f32 {
+        self.factors
+            .iter()
+            .map(|(_, value)| value)
+            .sum::<f32>()
+            .clamp(0.0, 1.0)
+    }
+
+    pub fn change(&self) -> f32 {
+        self.current - self.previous
+    }
+
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "Счастье: {:.0}% ({:+.1}), цель {:.0}%\n",
+            self.current * 100.0,
+            self.change() * 100.0,
+            self.target() * 100.0
+        );
+        for (factor, value) in &self.factors {
+            report.push_str(&format!("  {}: {:+.0}%\n", factor, value * 100.0));
+        }
+        report
+    }
+}
+
+// Списывает потребление всех классов из запасов города и возвращает
+// степень удовлетворения спроса на каждый ресурс (от 0.0 до 1.0)
+pub fn consume(population: &Population, resources: &mut Resources) -> HashMap<ResourceType, f32> {
+    let mut demand: HashMap<ResourceType, f32> = HashMap::new();
+    for (class, count) in &population.classes {
+        for (resource, per_capita) in basket(class, population.food_consumption) {
+            *demand.entry(resource).or_insert(0.0) += *count as f32 * per_capita;
+        }
+    }
+
+    // Дробный спрос округляется вверх: даже один знатный житель
+    // потребляет хотя бы единицу кристаллов
+    let mut needed: Vec<(ResourceType, u32)> = demand
+        .into_iter()
+        .map(|(resource, amount)| (resource, (amount - DEMAND_EPSILON).ceil().max(0.0) as u32))
+        .collect();
+    needed.sort_by_key(|(resource, _)| format!("{:?}", resource));
+
+    // Списываем сколько есть одной записью журнала
+    let applied = resources.apply_partial(LedgerEntry::cost(LedgerReason::Consumption, &needed));
+    let consumed = applied.net();
+
+    let mut satisfaction = HashMap::new();
+    for (resource, needed) in needed {
+        let consumed = consumed
+            .get(&resource)
+            .map(|delta| delta.unsigned_abs() as u32)
+            .unwrap_or(0);
+
+        let ratio = if needed == 0 {
+            1.0
+        } else {
+            consumed as f32 / needed as f32
+        };
+        satisfaction.insert(resource, ratio);
+    }
+
+    satisfaction
+}
+
+// Факторы счастья класса, не зависящие от зданий и технологий
+pub fn needs_factors(
+    class: &PopulationClass,
+    food_consumption: f32,
+    satisfaction: &HashMap<ResourceType, f32>,
+) -> Vec<(HappinessFactor, f32)> {
+    let mut factors = vec![(HappinessFactor::Base, BASE_HAPPINESS)];
+    let mut all_met = true;
+
+    for (resource, _) in basket(class, food_consumption) {
+        let ratio = *satisfaction.get(&resource).unwrap_or(&1.0);
+        if ratio < 1.0 {
+            all_met = false;
+            factors.push((
+                HappinessFactor::Shortage(resource.clone()),
+                -(1.0 - ratio) * shortage_weight(&resource),
+            ));
+        }
+    }
+
+    if all_met {
+        factors.push((HappinessFactor::NeedsMet, 0.1));
+    }
+
+    factors
+}
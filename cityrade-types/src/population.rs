@@ -1,3 +1,4 @@
+use crate::needs::{HAPPINESS_ADJUSTMENT_RATE, HappinessBreakdown, HappinessFactor};
 use crate::resources::ResourceType;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -12,10 +13,24 @@ pub enum PopulationClass {
     Noble,    // Знать - увеличивает престиж и налоги
 }
 
+impl PopulationClass {
+    // Классы, которые работают в зданиях и могут остаться без работы
+    pub fn labor_classes() -> [PopulationClass; 4] {
+        [
+            PopulationClass::Peasant,
+            PopulationClass::Worker,
+            PopulationClass::Merchant,
+            PopulationClass::Scholar,
+        ]
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Population {
     pub classes: HashMap<PopulationClass, u32>,
-    pub happiness: f32,        // от 0.0 до 1.0
+    pub happiness: f32, // среднее счастье всех жителей, от 0.0 до 1.0
+    pub class_happiness: HashMap<PopulationClass, f32>,
+    pub happiness_breakdown: HashMap<PopulationClass, HappinessBreakdown>,
     pub growth_rate: f32,      // базовый прирост населения
     pub food_consumption: f32, // сколько пищи потребляет каждая единица населения в день
     pub next_growth_tick: u32, // через сколько тиков произойдет рост
@@ -31,9 +46,13 @@ impl Population {
         classes.insert(PopulationClass::Scholar, 1);
         classes.insert(PopulationClass::Noble, 1);
 
+        let class_happiness = classes.keys().map(|class| (class.clone(), 0.7)).collect();
+
         Population {
             classes,
             happiness: 0.7,
+            class_happiness,
+            happiness_breakdown: HashMap::new(),
             growth_rate: 0.02,
             food_consumption: 0.5,
             next_growth_tick: 10,
@@ -44,25 +63,70 @@ impl Population {
         self.classes.values().sum()
     }
 
+    pub fn count(&self, class: &PopulationClass) -> u32 {
+        *self.classes.get(class).unwrap_or(&0)
+    }
+
+    pub fn add_citizens(&mut self, class: PopulationClass, amount: u32) {
+        *self.classes.entry(class).or_insert(0) += amount;
+    }
+
+    // Забирает жителей класса, если их хватает
+    pub fn remove_citizens(&mut self, class: &PopulationClass, amount: u32) -> bool {
+        match self.classes.get_mut(class) {
+            Some(count) if *count >= amount => {
+                *count -= amount;
+                true
+            }
+            _ => amount == 0,
+        }
+    }
+
     pub fn daily_food_consumption(&self) -> u32 {
         (self.total() as f32 * self.food_consumption) as u32
     }
 
-    pub fn update(&mut self, food_available: u32, housing_capacity: u32) {
-        // Расчет потребности в пище
-        let food_needed = self.daily_food_consumption();
+    pub fn class_happiness(&self, class: &PopulationClass) -> f32 {
+        *self.class_happiness.get(class).unwrap_or(&self.happiness)
+    }
 
-        // Обновление счастья на основе доступности пищи
-        if food_available < food_needed {
-            let food_ratio = food_available as f32 / food_needed as f32;
-            self.happiness -= (1.0 - food_ratio) * 0.1;
-        } else {
-            self.happiness += 0.01;
+    // Сдвигает счастье класса к цели, заданной факторами, и запоминает разбор
+    pub fn apply_happiness(
+        &mut self,
+        class: PopulationClass,
+        factors: Vec<(HappinessFactor, f32)>,
+    ) {
+        let previous = self.class_happiness(&class);
+        let mut breakdown = HappinessBreakdown {
+            factors,
+            previous,
+            current: previous,
+        };
+        breakdown.current = (previous
+            + (breakdown.target() - previous) * HAPPINESS_ADJUSTMENT_RATE)
+            .clamp(0.0, 1.0);
+
+        self.class_happiness
+            .insert(class.clone(), breakdown.current);
+        self.happiness_breakdown.insert(class, breakdown);
+        self.happiness = self.average_happiness();
+    }
+
+    // Среднее счастье, взвешенное по численности классов
+    pub fn average_happiness(&self) -> f32 {
+        let total = self.total();
+        if total == 0 {
+            return self.happiness;
         }
 
-        // Ограничиваем счастье в диапазоне 0.0-1.0
-        self.happiness = self.happiness.max(0.0).min(1.0);
+        self.classes
+            .iter()
+            .map(|(class, count)| self.class_happiness(class) * *count as f32)
+            .sum::<f32>()
+            / total as f32
+    }
 
+    pub fn update(&mut self, housing_capacity: u32) {
         // Обновление роста населения
         if self.next_growth_tick > 0 {
             self.next_growth_tick -= 1;
@@ -96,7 +160,7 @@ impl Population {
         }
     }
 
-    fn distribute_growth(&mut self, growth: u32) {
+    pub fn distribute_growth(&mut self, growth: u32) {
         // 60% прироста идет в крестьяне, 30% в рабочие, 10% распределяется по остальным
         let peasant_growth = (growth as f32 * 0.6) as u32;
         let worker_growth = (growth as f32 * 0.3) as u32;
@@ -124,7 +188,7 @@ impl Population {
         }
     }
 
-    fn distribute_decline(&mut self, decline: u32) {
+    pub fn distribute_decline(&mut self, decline: u32) {
         // Сначала уменьшаем число крестьян и рабочих
         let mut remaining_decline = decline;
 
@@ -147,6 +211,10 @@ impl Population {
     }
 
     pub fn get_production_bonus(&self, resource: &ResourceType) -> f32 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+
         // Разные классы дают бонусы к разным ресурсам
         match resource {
             ResourceType::Wood => {
@@ -164,11 +232,20 @@ impl Population {
                 let scholars = *self.classes.get(&PopulationClass::Scholar).unwrap_or(&0) as f32;
                 (merchants * 2.0 + nobles * 3.0 + scholars * 1.0) / self.total() as f32
             }
-            ResourceType::Food => todo!(),
-            ResourceType::Iron => todo!(),
-            ResourceType::Crystal => todo!(),
-            ResourceType::Population => todo!(),
-            ResourceType::Energy => todo!(),
+            ResourceType::Food => {
+                let peasants = *self.classes.get(&PopulationClass::Peasant).unwrap_or(&0) as f32;
+                peasants * 1.5 / self.total() as f32
+            }
+            ResourceType::Iron => {
+                let workers = *self.classes.get(&PopulationClass::Worker).unwrap_or(&0) as f32;
+                workers * 1.2 / self.total() as f32
+            }
+            ResourceType::Crystal => {
+                let workers = *self.classes.get(&PopulationClass::Worker).unwrap_or(&0) as f32;
+                let scholars = *self.classes.get(&PopulationClass::Scholar).unwrap_or(&0) as f32;
+                (workers * 0.5 + scholars * 2.0) / self.total() as f32
+            }
+            ResourceType::Population | ResourceType::Energy => 0.0,
         }
     }
 }
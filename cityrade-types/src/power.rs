@@ -0,0 +1,139 @@
+use crate::building::BuildingType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// Приоритет энергоснабжения здания по умолчанию
+pub const DEFAULT_POWER_PRIORITY: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerConsumer {
+    pub building_id: String,
+    pub building_type: BuildingType,
+    pub priority: u32,
+    pub demand: u32,
+    pub supplied: u32,
+}
+
+impl PowerConsumer {
+    // Доля потребности, покрытая сетью
+    pub fn ratio(&self) -> f32 {
+        if self.demand == 0 {
+            1.0
+        } else {
+            self.supplied as f32 / self.demand as f32
+        }
+    }
+}
+
+// Состояние энергосети города за последний тик
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PowerReport {
+    pub generation: u32,
+    pub stored: u32, // Запас энергии в начале тика
+    pub consumers: Vec<PowerConsumer>,
+}
+
+impl PowerReport {
+    pub fn demand(&self) -> u32 {
+        self.consumers.iter().map(|c| c.demand).sum()
+    }
+
+    pub fn consumed(&self) -> u32 {
+        self.consumers.iter().map(|c| c.supplied).sum()
+    }
+
+    // Изменение запаса энергии за тик
+    pub fn net(&self) -> i32 {
+        self.generation as i32 - self.consumed() as i32
+    }
+
+    pub fn is_brownout(&self) -> bool {
+        self.consumed() < self.demand()
+    }
+
+    // Доля энергии, которую получает здание. Здания без потребления работают всегда
+    pub fn ratio(&self, building_id: &str) -> f32 {
+        self.consumers
+            .iter()
+            .find(|c| c.building_id == building_id)
+            .map(|c| c.ratio())
+            .unwrap_or(1.0)
+    }
+
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "Выработка: {}, запас: {}, спрос: {}, потреблено: {}\n",
+            self.generation,
+            self.stored,
+            self.demand(),
+            self.consumed()
+        );
+        if self.is_brownout() {
+            report.push_str("Нехватка энергии!\n");
+        }
+        for consumer in &self.consumers {
+            report.push_str(&format!(
+                "  [{}] {}: {}/{} ({:.0}%)\n",
+                consumer.priority,
+                consumer.building_type.display_name(),
+                consumer.supplied,
+                consumer.demand,
+                consumer.ratio() * 100.0
+            ));
+        }
+        report
+    }
+}
+
+// Распределяет выработку и запас энергии между потребителями. Группы с
+// большим приоритетом снабжаются первыми; если на группу энергии не хватает,
+// все ее здания получают одинаковую долю потребности
+pub fn distribute(
+    generation: u32,
+    stored: u32,
+    demands: Vec<(String, BuildingType, u32)>,
+    priorities: &HashMap<String, u32>,
+) -> PowerReport {
+    let mut tiers: BTreeMap<u32, Vec<PowerConsumer>> = BTreeMap::new();
+    for (building_id, building_type, demand) in demands {
+        let priority = *priorities
+            .get(&building_id)
+            .unwrap_or(&DEFAULT_POWER_PRIORITY);
+        tiers.entry(priority).or_default().push(PowerConsumer {
+            building_id,
+            building_type,
+            priority,
+            demand,
+            supplied: 0,
+        });
+    }
+
+    let mut available = generation.saturating_add(stored);
+    let mut consumers = Vec::new();
+
+    for (_, mut tier) in tiers.into_iter().rev() {
+        tier.sort_by(|a, b| a.building_id.cmp(&b.building_id));
+        let tier_demand: u32 = tier.iter().map(|c| c.demand).sum();
+
+        if tier_demand <= available {
+            for consumer in tier.iter_mut() {
+                consumer.supplied = consumer.demand;
+            }
+            available -= tier_demand;
+        } else {
+            let share = available as f32 / tier_demand as f32;
+            for consumer in tier.iter_mut() {
+                consumer.supplied = (consumer.demand as f32 * share) as u32;
+                available = available.saturating_sub(consumer.supplied);
+            }
+        }
+
+        consumers.extend(tier);
+    }
+
+    PowerReport {
+        generation,
+        stored,
+        consumers,
+    }
+}
@@ -0,0 +1,82 @@
+use crate::building::BuildingType;
+use crate::population::PopulationClass;
+use crate::resources::ResourceType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ProductionSource {
+    Base,
+    Building {
+        building_id: String,
+        building_type: BuildingType,
+    },
+    // Ресурсы, которые здание потребляет для работы
+    Upkeep {
+        building_id: String,
+        building_type: BuildingType,
+    },
+    Terrain,
+    Technology,
+    Population, // Бонусы классов населения
+    Taxes(PopulationClass),
+}
+
+impl fmt::Display for ProductionSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProductionSource::Base => write!(f, "Базовое производство"),
+            ProductionSource::Building { building_type, .. } => {
+                write!(f, "{}", building_type.display_name())
+            }
+            ProductionSource::Upkeep { building_type, .. } => {
+                write!(f, "Потребление: {}", building_type.display_name())
+            }
+            ProductionSource::Terrain => write!(f, "Местность"),
+            ProductionSource::Technology => write!(f, "Технологии"),
+            ProductionSource::Population => write!(f, "Бонусы населения"),
+            ProductionSource::Taxes(class) => write!(f, "Налоги: {:?}", class),
+        }
+    }
+}
+
+// Из чего складывается скорость производства каждого ресурса за последний тик.
+// Сумма вкладов ресурса равна его итоговой скорости производства
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProductionBreakdown {
+    pub sources: HashMap<ResourceType, Vec<(ProductionSource, i32)>>,
+}
+
+impl ProductionBreakdown {
+    pub fn add(&mut self, resource: ResourceType, source: ProductionSource, amount: i32) {
+        if amount != 0 {
+            self.sources
+                .entry(resource)
+                .or_default()
+                .push((source, amount));
+        }
+    }
+
+    pub fn sources(&self, resource: &ResourceType) -> &[(ProductionSource, i32)] {
+        self.sources
+            .get(resource)
+            .map(|sources| sources.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn total(&self, resource: &ResourceType) -> i32 {
+        self.sources(resource)
+            .iter()
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+
+    pub fn report(&self, resource: &ResourceType) -> String {
+        let mut report = format!("{}: {:+}\n", resource, self.total(resource));
+        for (source, amount) in self.sources(resource) {
+            report.push_str(&format!("  {}: {:+}\n", source, amount));
+        }
+        report
+    }
+}
@@ -1,4 +1,5 @@
 // cityrade-types/src/resources.rs
+use crate::ledger::{Ledger, LedgerEntry, LedgerReason};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
@@ -35,12 +36,20 @@ impl fmt::Display for ResourceType {
 pub struct Resources {
     resources: HashMap<ResourceType, u32>,
     production_rate: HashMap<ResourceType, i32>,
+    // Вместимость хранилищ. Ресурсы без записи хранятся без ограничений
+    #[serde(default)]
+    capacity: HashMap<ResourceType, u32>,
+    // Сколько ресурсов пропало из-за нехватки места с последнего take_overflow
+    #[serde(default)]
+    overflow: HashMap<ResourceType, u32>,
+    // Журнал последних операций с ресурсами
+    #[serde(default)]
+    ledger: Ledger,
 }
 
 impl Resources {
     pub fn new() -> Self {
         let mut resources = HashMap::new();
-        let mut production_rate = HashMap::new();
 
         // Стандартные ресурсы
         resources.insert(ResourceType::Gold, 100);
@@ -52,19 +61,13 @@ impl Resources {
         resources.insert(ResourceType::Population, 10);
         resources.insert(ResourceType::Energy, 50);
 
-        // Начальная скорость производства
-        production_rate.insert(ResourceType::Gold, 5);
-        production_rate.insert(ResourceType::Wood, 8);
-        production_rate.insert(ResourceType::Stone, 3);
-        production_rate.insert(ResourceType::Food, 10);
-        production_rate.insert(ResourceType::Iron, 0);
-        production_rate.insert(ResourceType::Crystal, 0);
-        production_rate.insert(ResourceType::Population, 1);
-        production_rate.insert(ResourceType::Energy, 2);
-
+        // Скорость производства задает город на каждом тике
         Resources {
             resources,
-            production_rate,
+            production_rate: HashMap::new(),
+            capacity: HashMap::new(),
+            overflow: HashMap::new(),
+            ledger: Ledger::default(),
         }
     }
 
@@ -80,22 +83,56 @@ impl Resources {
         *self.resources.get(resource).unwrap_or(&0)
     }
 
-    pub fn set(&mut self, resource: ResourceType, amount: u32) {
-        self.resources.insert(resource, amount);
+    // Меняет количество в обход журнала. Снаружи ресурсы меняются только
+    // записями через apply и apply_partial
+    fn set(&mut self, resource: ResourceType, amount: u32) {
+        let stored = self.store(&resource, amount);
+        self.resources.insert(resource, stored);
+    }
+
+    // Возвращает количество, не поместившееся в хранилище
+    fn add(&mut self, resource: &ResourceType, amount: u32) -> u32 {
+        let total = self.get(resource).saturating_add(amount);
+        let stored = self.store(resource, total);
+        self.resources.insert(resource.clone(), stored);
+        total - stored
+    }
+
+    fn subtract(&mut self, resource: &ResourceType, amount: u32) -> bool {
+        match self.get(resource).checked_sub(amount) {
+            Some(remaining) => {
+                self.resources.insert(resource.clone(), remaining);
+                true
+            }
+            None => false,
+        }
     }
 
-    pub fn add(&mut self, resource: &ResourceType, amount: u32) {
-        let current = self.get(resource);
-        self.resources.insert(resource.clone(), current + amount);
+    pub fn capacity(&self, resource: &ResourceType) -> u32 {
+        *self.capacity.get(resource).unwrap_or(&u32::MAX)
     }
 
-    pub fn subtract(&mut self, resource: &ResourceType, amount: u32) -> bool {
-        let current = self.get(resource);
-        if current >= amount {
-            self.resources.insert(resource.clone(), current - amount);
-            true
+    // Задает вместимость хранилища. Излишек сверх новой вместимости пропадает
+    pub fn set_capacity(&mut self, resource: ResourceType, capacity: u32) {
+        self.capacity.insert(resource.clone(), capacity);
+        let current = self.get(&resource);
+        self.set(resource, current);
+    }
+
+    // Забирает накопленные потери от переполнения
+    pub fn take_overflow(&mut self) -> HashMap<ResourceType, u32> {
+        std::mem::take(&mut self.overflow)
+    }
+
+    // Обрезает количество по вместимости и запоминает потерю
+    fn store(&mut self, resource: &ResourceType, amount: u32) -> u32 {
+        let capacity = self.capacity(resource);
+        if amount > capacity {
+            let lost = self.overflow.entry(resource.clone()).or_insert(0);
+            *lost = lost.saturating_add(amount - capacity);
+            capacity
         } else {
-            false
+            amount
         }
     }
 
@@ -107,16 +144,84 @@ impl Resources {
         self.production_rate.insert(resource, rate);
     }
 
+    // Заменяет все скорости производства. Ресурсы без записи не производятся
+    pub fn set_production_rates(&mut self, rates: HashMap<ResourceType, i32>) {
+        self.production_rate = rates;
+    }
+
     pub fn update_production(&mut self) {
-        for (resource, rate) in self.production_rate.clone() {
+        let mut rates: Vec<_> = self.production_rate.clone().into_iter().collect();
+        rates.sort_by_key(|(resource, _)| format!("{:?}", resource));
+
+        let mut entry = LedgerEntry::new(LedgerReason::Production);
+        for (resource, rate) in rates {
             if rate > 0 {
-                self.add(&resource, rate as u32);
+                entry = entry.credit(resource, rate.unsigned_abs());
             } else if rate < 0 {
-                // Игнорируем результат вычитания - если ресурсов не хватает,
-                // они просто становятся равными нулю
-                let _ = self.subtract(&resource, (-rate) as u32);
+                entry = entry.debit(resource, rate.unsigned_abs());
+            }
+        }
+        // Если ресурсов не хватает, они просто становятся равными нулю
+        self.apply_partial(entry);
+    }
+
+    // Применяет запись целиком: если хотя бы одного ресурса не хватает,
+    // ничего не меняется. Возвращает фактически проведенные изменения
+    // (зачисление может обрезаться вместимостью хранилища)
+    pub fn apply(&mut self, entry: LedgerEntry) -> Result<LedgerEntry, String> {
+        let mut net: Vec<_> = entry.net().into_iter().collect();
+        net.sort_by_key(|(resource, _)| format!("{:?}", resource));
+
+        for (resource, delta) in &net {
+            if *delta < 0 && (self.get(resource) as i64) < -delta {
+                return Err(format!(
+                    "Недостаточно ресурса \"{}\": нужно {}, есть {}",
+                    resource,
+                    -delta,
+                    self.get(resource)
+                ));
             }
         }
+
+        Ok(self.commit(entry.reason, net))
+    }
+
+    // Применяет запись, списывая не больше, чем есть в наличии.
+    // Возвращает фактически проведенные изменения
+    pub fn apply_partial(&mut self, entry: LedgerEntry) -> LedgerEntry {
+        let mut net: Vec<_> = entry.net().into_iter().collect();
+        net.sort_by_key(|(resource, _)| format!("{:?}", resource));
+
+        let clamped = net
+            .into_iter()
+            .map(|(resource, delta)| {
+                let delta = delta.max(-(self.get(&resource) as i64));
+                (resource, delta)
+            })
+            .collect();
+
+        self.commit(entry.reason, clamped)
+    }
+
+    // Проводит заранее проверенные изменения и записывает их в журнал
+    fn commit(&mut self, reason: LedgerReason, net: Vec<(ResourceType, i64)>) -> LedgerEntry {
+        let mut applied = LedgerEntry::new(reason);
+        for (resource, delta) in net {
+            let amount = delta.unsigned_abs().min(u32::MAX as u64) as u32;
+            if delta > 0 {
+                let lost = self.add(&resource, amount);
+                applied.deltas.push((resource, (amount - lost) as i64));
+            } else if delta < 0 {
+                self.subtract(&resource, amount);
+                applied.deltas.push((resource, delta));
+            }
+        }
+        self.ledger.record(applied.clone());
+        applied
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
     }
 
     pub fn can_afford(&self, costs: &[(ResourceType, u32)]) -> bool {
@@ -125,15 +230,8 @@ impl Resources {
             .all(|(resource, amount)| self.get(resource) >= *amount)
     }
 
-    pub fn pay(&mut self, costs: &[(ResourceType, u32)]) -> bool {
-        if self.can_afford(costs) {
-            for (resource, amount) in costs {
-                self.subtract(resource, *amount);
-            }
-            true
-        } else {
-            false
-        }
+    pub fn pay(&mut self, reason: LedgerReason, costs: &[(ResourceType, u32)]) -> bool {
+        self.apply(LedgerEntry::cost(reason, costs)).is_ok()
     }
 
     pub fn get_all_resources(&self) -> Vec<(ResourceType, u32)> {
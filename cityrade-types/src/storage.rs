@@ -0,0 +1,93 @@
+use crate::building::{Building, BuildingType};
+use crate::resources::ResourceType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Доля запаса еды, которая портится за тик
+pub const FOOD_SPOILAGE_RATE: f32 = 0.02;
+// Во сколько раз амбары замедляют порчу
+pub const GRANARY_SPOILAGE_FACTOR: f32 = 0.5;
+
+// Вместимость хранилищ города без складов. Золото и население не ограничены
+pub fn base_capacity(resource: &ResourceType) -> Option<u32> {
+    match resource {
+        ResourceType::Wood => Some(500),
+        ResourceType::Stone => Some(500),
+        ResourceType::Food => Some(400),
+        ResourceType::Iron => Some(200),
+        ResourceType::Crystal => Some(100),
+        ResourceType::Energy => Some(200),
+        ResourceType::Gold | ResourceType::Population => None,
+    }
+}
+
+// Вместимость хранилищ с учетом зданий
+pub fn capacities<'a>(
+    buildings: impl IntoIterator<Item = &'a Building>,
+) -> HashMap<ResourceType, u32> {
+    let mut capacities: HashMap<ResourceType, u32> = [
+        ResourceType::Gold,
+        ResourceType::Wood,
+        ResourceType::Stone,
+        ResourceType::Food,
+        ResourceType::Iron,
+        ResourceType::Crystal,
+        ResourceType::Population,
+        ResourceType::Energy,
+    ]
+    .into_iter()
+    .filter_map(|resource| base_capacity(&resource).map(|capacity| (resource, capacity)))
+    .collect();
+
+    for building in buildings {
+        for (resource, amount) in building.building_type.storage(building.level) {
+            let capacity = capacities.entry(resource).or_insert(0);
+            *capacity = capacity.saturating_add(amount);
+        }
+    }
+
+    capacities
+}
+
+// Доля еды, которая испортится за тик
+pub fn spoilage_rate<'a>(buildings: impl IntoIterator<Item = &'a Building>) -> f32 {
+    let has_granary = buildings
+        .into_iter()
+        .any(|b| b.building_type == BuildingType::Granary);
+    if has_granary {
+        FOOD_SPOILAGE_RATE * GRANARY_SPOILAGE_FACTOR
+    } else {
+        FOOD_SPOILAGE_RATE
+    }
+}
+
+// Состояние хранилищ города за последний тик
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageReport {
+    pub capacity: HashMap<ResourceType, u32>,
+    pub overflow: HashMap<ResourceType, u32>, // Не поместилось в хранилища
+    pub spoiled: HashMap<ResourceType, u32>,  // Испортилось
+}
+
+impl StorageReport {
+    pub fn report(&self) -> String {
+        let mut resources: Vec<&ResourceType> = self.capacity.keys().collect();
+        resources.sort_by_key(|resource| format!("{:?}", resource));
+
+        let mut report = String::new();
+        for resource in resources {
+            report.push_str(&format!(
+                "{}: вместимость {}",
+                resource, self.capacity[resource]
+            ));
+            if let Some(lost) = self.overflow.get(resource) {
+                report.push_str(&format!(", пропало {}", lost));
+            }
+            if let Some(spoiled) = self.spoiled.get(resource) {
+                report.push_str(&format!(", испортилось {}", spoiled));
+            }
+            report.push('\n');
+        }
+        report
+    }
+}
@@ -1,5 +1,9 @@
+use crate::building::BuildingType;
+use crate::resources::ResourceType;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TechnologyType {
@@ -26,100 +30,211 @@ pub enum TechnologyType {
     Administration,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl TechnologyType {
+    pub fn all() -> [TechnologyType; 14] {
+        [
+            TechnologyType::Agriculture,
+            TechnologyType::Mining,
+            TechnologyType::Forestry,
+            TechnologyType::Trade,
+            TechnologyType::Banking,
+            TechnologyType::BasicConstruction,
+            TechnologyType::AdvancedConstruction,
+            TechnologyType::StoneWorks,
+            TechnologyType::BasicMilitary,
+            TechnologyType::AdvancedMilitary,
+            TechnologyType::Fortification,
+            TechnologyType::Education,
+            TechnologyType::Culture,
+            TechnologyType::Administration,
+        ]
+    }
+}
+
+impl FromStr for TechnologyType {
+    type Err = String;
+
+    // Разбирает название технологии без учета регистра и разделителей
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized: String = s
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+
+        TechnologyType::all()
+            .into_iter()
+            .find(|tech| format!("{:?}", tech).to_lowercase() == normalized)
+            .ok_or_else(|| format!("Неизвестная технология: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CityStat {
+    Happiness,
+    Defense,
+    Culture,
+    MaxPopulation,
+    MaxBuildings,
+}
+
+impl fmt::Display for CityStat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CityStat::Happiness => write!(f, "Счастье"),
+            CityStat::Defense => write!(f, "Защита"),
+            CityStat::Culture => write!(f, "Культура"),
+            CityStat::MaxPopulation => write!(f, "Максимальное население"),
+            CityStat::MaxBuildings => write!(f, "Максимум зданий"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TechEffect {
+    // Множитель производства ресурса (1.2 = +20%)
+    ProductionMultiplier {
+        resource: ResourceType,
+        multiplier: f32,
+    },
+    // Доля скидки на строительство и улучшение зданий (0.1 = 10%)
+    ConstructionDiscount(f32),
+    UnlockBuilding(BuildingType),
+    UnlockTradeRoutes,
+    StatBonus {
+        stat: CityStat,
+        amount: i32,
+    },
+}
+
+impl fmt::Display for TechEffect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TechEffect::ProductionMultiplier {
+                resource,
+                multiplier,
+            } => {
+                let percent = ((multiplier - 1.0) * 100.0).round() as i32;
+                if percent >= 0 {
+                    write!(
+                        f,
+                        "Увеличивает производство ресурса \"{}\" на {}%",
+                        resource, percent
+                    )
+                } else {
+                    write!(
+                        f,
+                        "Снижает производство ресурса \"{}\" на {}%",
+                        resource, -percent
+                    )
+                }
+            }
+            TechEffect::ConstructionDiscount(discount) => {
+                let percent = (discount * 100.0).round() as i32;
+                write!(f, "Снижает стоимость строительства на {}%", percent)
+            }
+            TechEffect::UnlockBuilding(building_type) => {
+                write!(f, "Позволяет строить: {}", building_type.display_name())
+            }
+            TechEffect::UnlockTradeRoutes => {
+                write!(f, "Позволяет устанавливать торговые маршруты")
+            }
+            TechEffect::StatBonus { stat, amount } => write!(f, "{}: {:+}", stat, amount),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Technology {
     pub tech_type: TechnologyType,
     pub name: String,
     pub description: String,
     pub cost: u32,
     pub prerequisites: Vec<TechnologyType>,
-    pub unlock_effects: Vec<String>,
+    pub unlock_effects: Vec<TechEffect>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Technology {
+    // Текстовое описание эффектов для интерфейса
+    pub fn effect_descriptions(&self) -> Vec<String> {
+        self.unlock_effects.iter().map(|e| e.to_string()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TechnologyTree {
     technologies: HashMap<TechnologyType, Technology>,
 }
 
 impl TechnologyTree {
-    pub fn new() -> Self {
-        let mut technologies = HashMap::new();
-
-        // Базовые технологии (без предпосылок)
-        technologies.insert(TechnologyType::Agriculture, Technology {
-            tech_type: TechnologyType::Agriculture,
-            name: "Сельское хозяйство".to_string(),
-            description: "Улучшает производство пищи для населения".to_string(),
-            cost: 100,
-            prerequisites: Vec::new(),
-            unlock_effects: vec![
-                "Увеличивает производство пищи на 20%".to_string(),
-                "Позволяет строить фермы".to_string(),
-            ],
-        });
+    pub fn from_technologies(technologies: Vec<Technology>) -> Self {
+        let mut tree = TechnologyTree::default();
+        for technology in technologies {
+            tree.insert(technology);
+        }
+        tree
+    }
 
-        technologies.insert(TechnologyType::Mining, Technology {
-            tech_type: TechnologyType::Mining,
-            name: "Горное дело".to_string(),
-            description: "Улучшает добычу камня и других минералов".to_string(),
-            cost: 100,
-            prerequisites: Vec::new(),
-            unlock_effects: vec![
-                "Увеличивает добычу камня на 20%".to_string(),
-                "Позволяет строить шахты".to_string(),
-            ],
-        });
+    // Добавляет технологию, заменяя существующую того же типа
+    pub fn insert(&mut self, technology: Technology) {
+        self.technologies
+            .insert(technology.tech_type.clone(), technology);
+    }
 
-        technologies.insert(TechnologyType::Forestry, Technology {
-            tech_type: TechnologyType::Forestry,
-            name: "Лесное хозяйство".to_string(),
-            description: "Улучшает заготовку древесины".to_string(),
-            cost: 100,
-            prerequisites: Vec::new(),
-            unlock_effects: vec![
-                "Увеличивает производство дерева на 20%".to_string(),
-                "Позволяет строить лесопилки".to_string(),
-            ],
-        });
+    // Проверяет целостность дерева: ключи совпадают с типами,
+    // все предпосылки существуют и в графе нет циклов
+    pub fn validate(&self) -> Result<(), String> {
+        for (tech_type, tech) in &self.technologies {
+            if &tech.tech_type != tech_type {
+                return Err(format!(
+                    "Технология {:?} записана под ключом {:?}",
+                    tech.tech_type, tech_type
+                ));
+            }
 
-        technologies.insert(TechnologyType::BasicConstruction, Technology {
-            tech_type: TechnologyType::BasicConstruction,
-            name: "Основы строительства".to_string(),
-            description: "Базовые принципы строительства зданий".to_string(),
-            cost: 100,
-            prerequisites: Vec::new(),
-            unlock_effects: vec![
-                "Позволяет строить базовые здания".to_string(),
-                "Снижает стоимость строительства на 10%".to_string(),
-            ],
-        });
+            for prerequisite in &tech.prerequisites {
+                if !self.technologies.contains_key(prerequisite) {
+                    return Err(format!(
+                        "Технология {:?} требует отсутствующую технологию {:?}",
+                        tech_type, prerequisite
+                    ));
+                }
+            }
+        }
 
-        // Технологии второго уровня
-        technologies.insert(TechnologyType::Trade, Technology {
-            tech_type: TechnologyType::Trade,
-            name: "Торговля".to_string(),
-            description: "Развивает торговые отношения с другими городами".to_string(),
-            cost: 200,
-            prerequisites: vec![TechnologyType::Agriculture],
-            unlock_effects: vec![
-                "Позволяет строить рынки".to_string(),
-                "Позволяет устанавливать торговые маршруты".to_string(),
-            ],
-        });
+        // Поиск в глубину: технология в `visiting` встречена повторно - цикл
+        let mut visited = HashSet::new();
+        let mut visiting = HashSet::new();
+        for tech_type in self.technologies.keys() {
+            self.check_cycles(tech_type, &mut visiting, &mut visited)?;
+        }
 
-        technologies.insert(TechnologyType::AdvancedConstruction, Technology {
-            tech_type: TechnologyType::AdvancedConstruction,
-            name: "Продвинутое строительство".to_string(),
-            description: "Усовершенствованные методы строительства".to_string(),
-            cost: 200,
-            prerequisites: vec![TechnologyType::BasicConstruction, TechnologyType::Mining],
-            unlock_effects: vec![
-                "Позволяет строить продвинутые здания".to_string(),
-                "Снижает стоимость строительства на дополнительные 15%".to_string(),
-            ],
-        });
+        Ok(())
+    }
+
+    fn check_cycles<'a>(
+        &'a self,
+        tech_type: &'a TechnologyType,
+        visiting: &mut HashSet<&'a TechnologyType>,
+        visited: &mut HashSet<&'a TechnologyType>,
+    ) -> Result<(), String> {
+        if visited.contains(tech_type) {
+            return Ok(());
+        }
+        if !visiting.insert(tech_type) {
+            return Err(format!(
+                "Циклическая зависимость технологий через {:?}",
+                tech_type
+            ));
+        }
+
+        for prerequisite in &self.technologies[tech_type].prerequisites {
+            self.check_cycles(prerequisite, visiting, visited)?;
+        }
 
-        TechnologyTree { technologies }
+        visiting.remove(tech_type);
+        visited.insert(tech_type);
+        Ok(())
     }
 
     pub fn get_all_technologies(&self) -> &HashMap<TechnologyType, Technology> {
@@ -129,4 +244,173 @@ impl TechnologyTree {
     pub fn get_technology(&self, tech_type: &TechnologyType) -> Option<&Technology> {
         self.technologies.get(tech_type)
     }
+
+    // Технологии дерева в порядке объявления TechnologyType, чтобы результат
+    // не зависел от порядка обхода HashMap
+    fn ordered(&self) -> impl Iterator<Item = &Technology> {
+        TechnologyType::all()
+            .into_iter()
+            .filter_map(|tech_type| self.technologies.get(&tech_type))
+    }
+
+    // Технология, открывающая тип здания. None - здание доступно сразу.
+    // Если здание открывают несколько технологий, берется первая по порядку
+    pub fn required_technology(&self, building_type: &BuildingType) -> Option<&Technology> {
+        self.ordered().find(|tech| {
+            tech.unlock_effects
+                .contains(&TechEffect::UnlockBuilding(building_type.clone()))
+        })
+    }
+
+    // Эффекты всех изученных технологий в порядке объявления технологий
+    pub fn effects_of<'t, 's>(
+        &'t self,
+        completed: &'s HashSet<TechnologyType>,
+    ) -> impl Iterator<Item = &'t TechEffect> + use<'t, 's> {
+        self.ordered()
+            .filter(|tech| completed.contains(&tech.tech_type))
+            .flat_map(|tech| tech.unlock_effects.iter())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ResearchStatus {
+    Completed,
+    InProgress,
+    Queued,
+    Available, // Все предпосылки изучены
+    Locked,    // Не хватает предпосылок
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ResearchEvent {
+    Completed(TechnologyType),
+}
+
+// Состояние исследований отдельного города
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResearchState {
+    pub completed: HashSet<TechnologyType>,
+    pub queue: VecDeque<TechnologyType>,
+    pub progress: u32, // Очки, вложенные в первую технологию очереди
+}
+
+impl ResearchState {
+    pub fn new() -> Self {
+        ResearchState::default()
+    }
+
+    pub fn is_researched(&self, tech_type: &TechnologyType) -> bool {
+        self.completed.contains(tech_type)
+    }
+
+    pub fn current(&self) -> Option<&TechnologyType> {
+        self.queue.front()
+    }
+
+    pub fn status(&self, tree: &TechnologyTree, tech_type: &TechnologyType) -> ResearchStatus {
+        if self.is_researched(tech_type) {
+            return ResearchStatus::Completed;
+        }
+        if self.current() == Some(tech_type) {
+            return ResearchStatus::InProgress;
+        }
+        if self.queue.contains(tech_type) {
+            return ResearchStatus::Queued;
+        }
+
+        match tree.get_technology(tech_type) {
+            Some(tech) if tech.prerequisites.iter().all(|p| self.is_researched(p)) => {
+                ResearchStatus::Available
+            }
+            _ => ResearchStatus::Locked,
+        }
+    }
+
+    // Ставит технологию в очередь. Предпосылки должны быть изучены
+    // или стоять в очереди раньше
+    pub fn enqueue(
+        &mut self,
+        tree: &TechnologyTree,
+        tech_type: TechnologyType,
+    ) -> Result<(), String> {
+        let tech = tree
+            .get_technology(&tech_type)
+            .ok_or_else(|| format!("Технология {:?} не найдена", tech_type))?;
+
+        if self.is_researched(&tech_type) {
+            return Err(format!("Технология \"{}\" уже изучена", tech.name));
+        }
+
+        if self.queue.contains(&tech_type) {
+            return Err(format!("Технология \"{}\" уже в очереди", tech.name));
+        }
+
+        for prerequisite in &tech.prerequisites {
+            if !self.is_researched(prerequisite) && !self.queue.contains(prerequisite) {
+                let name = tree
+                    .get_technology(prerequisite)
+                    .map(|t| t.name.clone())
+                    .unwrap_or_else(|| format!("{:?}", prerequisite));
+                return Err(format!("Сначала нужно изучить \"{}\"", name));
+            }
+        }
+
+        self.queue.push_back(tech_type);
+        Ok(())
+    }
+
+    // Убирает технологию из очереди вместе с зависящими от нее
+    pub fn dequeue(&mut self, tree: &TechnologyTree, tech_type: &TechnologyType) -> bool {
+        let Some(index) = self.queue.iter().position(|t| t == tech_type) else {
+            return false;
+        };
+
+        if index == 0 {
+            self.progress = 0;
+        }
+
+        let mut removed = vec![self.queue.remove(index).unwrap()];
+        self.queue.retain(|queued| {
+            let depends = tree
+                .get_technology(queued)
+                .is_some_and(|t| t.prerequisites.iter().any(|p| removed.contains(p)));
+            if depends {
+                removed.push(queued.clone());
+            }
+            !depends
+        });
+
+        true
+    }
+
+    // Вкладывает очки исследований в очередь. Излишек переходит
+    // на следующую технологию
+    pub fn add_progress(&mut self, tree: &TechnologyTree, points: u32) -> Vec<ResearchEvent> {
+        let mut events = Vec::new();
+        let mut points = points;
+
+        while let Some(current) = self.queue.front().cloned() {
+            let Some(tech) = tree.get_technology(&current) else {
+                // Технология исчезла из дерева - пропускаем ее
+                self.queue.pop_front();
+                self.progress = 0;
+                continue;
+            };
+
+            let needed = tech.cost.saturating_sub(self.progress);
+            if points < needed {
+                self.progress += points;
+                break;
+            }
+
+            points -= needed;
+            self.progress = 0;
+            self.queue.pop_front();
+            self.completed.insert(current.clone());
+            events.push(ResearchEvent::Completed(current));
+        }
+
+        events
+    }
 }
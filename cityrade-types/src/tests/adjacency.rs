@@ -0,0 +1,73 @@
+use super::{city, place};
+use crate::adjacency::{AdjacencyBonus, AdjacencyEffect};
+use crate::building::BuildingType;
+use crate::city::City;
+use crate::production::ProductionSource;
+use crate::resources::ResourceType;
+
+// Выработка еды конкретным зданием за последний тик
+fn food_from(city: &City, building_id: &str) -> i32 {
+    city.production
+        .sources(&ResourceType::Food)
+        .iter()
+        .filter_map(|(source, amount)| match source {
+            ProductionSource::Building {
+                building_id: id, ..
+            } if id == building_id => Some(*amount),
+            _ => None,
+        })
+        .sum()
+}
+
+#[test]
+fn preview_lists_bonuses_for_both_sides() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let farm = place(&mut city, BuildingType::Farm, (1, 1));
+    place(&mut city, BuildingType::Workshop, (5, 5));
+
+    // Мельница сама бонусов не получает, но усиливает ферму
+    let preview = city.preview_adjacency(&BuildingType::WaterMill, (2, 2));
+    assert_eq!(
+        preview,
+        [AdjacencyBonus {
+            building_id: Some(farm.clone()),
+            neighbor_id: None,
+            effect: AdjacencyEffect::Production {
+                resource: ResourceType::Food,
+                bonus: 0.2,
+            },
+        }]
+    );
+
+    // Вдали от соседей бонусов нет
+    assert!(
+        city.preview_adjacency(&BuildingType::WaterMill, (3, 3))
+            .is_empty()
+    );
+}
+
+#[test]
+fn preview_matches_applied_bonus() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let farm = place(&mut city, BuildingType::Farm, (1, 1));
+    city.update_resource_production();
+    let before = food_from(&city, &farm);
+    assert!(before > 0);
+
+    let preview = city.preview_adjacency(&BuildingType::WaterMill, (2, 1));
+    let mill = place(&mut city, BuildingType::WaterMill, (2, 1));
+    let applied = city.adjacency_bonuses();
+
+    assert_eq!(preview.len(), applied.len());
+    for (previewed, bonus) in preview.iter().zip(&applied) {
+        assert_eq!(previewed.building_id, bonus.building_id);
+        assert_eq!(bonus.neighbor_id.as_deref(), Some(mill.as_str()));
+        assert_eq!(previewed.effect, bonus.effect);
+    }
+
+    city.update_resource_production();
+    assert_eq!(
+        food_from(&city, &farm),
+        (before as f32 * 1.2).round() as i32
+    );
+}
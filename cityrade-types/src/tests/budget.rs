@@ -0,0 +1,90 @@
+use super::city;
+use crate::budget::{BudgetItem, MAX_TAX_RATE, TaxPolicy};
+use crate::population::{Population, PopulationClass};
+use crate::resources::ResourceType;
+
+#[test]
+fn taxes_are_collected_per_class() {
+    let population = Population::new();
+    let taxes = TaxPolicy::default().collect(&population);
+
+    // Ученые при ставке 5% не набирают и одной монеты, солдаты не платят
+    assert_eq!(
+        taxes,
+        [
+            (PopulationClass::Merchant, 1),
+            (PopulationClass::Noble, 1),
+            (PopulationClass::Peasant, 2),
+            (PopulationClass::Worker, 1),
+        ]
+    );
+}
+
+#[test]
+fn tax_income_grows_with_rate() {
+    let population = Population::new();
+    let mut policy = TaxPolicy::default();
+    policy
+        .set_rate(PopulationClass::Noble, MAX_TAX_RATE)
+        .unwrap();
+    policy.set_rate(PopulationClass::Peasant, 0.0).unwrap();
+
+    let taxes = policy.collect(&population);
+
+    assert!(taxes.contains(&(PopulationClass::Noble, 4)));
+    assert!(
+        !taxes
+            .iter()
+            .any(|(class, _)| *class == PopulationClass::Peasant)
+    );
+}
+
+#[test]
+fn rate_is_clamped_to_maximum() {
+    let mut policy = TaxPolicy::default();
+
+    assert!(
+        policy
+            .set_rate(PopulationClass::Merchant, MAX_TAX_RATE + 0.01)
+            .is_err()
+    );
+    assert!(policy.set_rate(PopulationClass::Merchant, -0.1).is_err());
+    assert!(
+        policy
+            .set_rate(PopulationClass::Merchant, f32::NAN)
+            .is_err()
+    );
+    assert!(
+        policy
+            .set_rate(PopulationClass::Merchant, f32::INFINITY)
+            .is_err()
+    );
+    assert_eq!(policy.rate(&PopulationClass::Merchant), 0.15);
+
+    policy
+        .set_rate(PopulationClass::Merchant, MAX_TAX_RATE)
+        .unwrap();
+    assert_eq!(policy.rate(&PopulationClass::Merchant), MAX_TAX_RATE);
+}
+
+#[test]
+fn city_budget_lists_taxes_by_class() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    city.set_tax_rate(PopulationClass::Noble, MAX_TAX_RATE)
+        .unwrap();
+
+    city.update_resource_production();
+
+    let income = &city.budget.income;
+    assert!(income.contains(&(BudgetItem::Taxes(PopulationClass::Noble), 4)));
+    assert!(income.contains(&(BudgetItem::Taxes(PopulationClass::Peasant), 2)));
+    assert!(
+        !income
+            .iter()
+            .any(|(item, _)| *item == BudgetItem::Taxes(PopulationClass::Soldier))
+    );
+    assert_eq!(
+        city.resources.get_production_rate(&ResourceType::Gold),
+        city.budget.balance()
+    );
+}
@@ -0,0 +1,114 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::caravan::{Caravan, CaravanStatus, plan_path};
+use crate::city::City;
+use crate::market::{ContractStatus, TradeRoute};
+use crate::resources::ResourceType;
+use crate::world::WorldMap;
+use rand::{SeedableRng, rngs::StdRng};
+use std::collections::HashMap;
+
+fn route(escort: u32) -> TradeRoute {
+    TradeRoute {
+        id: "route".to_string(),
+        source_city: "Источник".to_string(),
+        target_city: "Получатель".to_string(),
+        resource: ResourceType::Wood,
+        quantity: 10,
+        price_per_unit: 3,
+        duration: 0,
+        escort,
+        status: ContractStatus::Accepted,
+        caravan_id: None,
+    }
+}
+
+fn cities() -> HashMap<String, City> {
+    [
+        city("Источник", "alice", (0, 0)),
+        city("Получатель", "alice", (20, 5)),
+    ]
+    .into_iter()
+    .map(|city| (city.name.clone(), city))
+    .collect()
+}
+
+fn caravan(cities: &HashMap<String, City>, escort: u32) -> Caravan {
+    Caravan::new(&route(escort), &cities["Источник"], &cities["Получатель"])
+}
+
+// Проводит караван до конца пути
+fn run(caravan: &mut Caravan, cities: &HashMap<String, City>, seed: u64) -> CaravanStatus {
+    let world = WorldMap::new(64, 64);
+    let mut rng = StdRng::seed_from_u64(seed);
+    while caravan.status == CaravanStatus::InTransit {
+        caravan.advance(&world, cities, &mut rng);
+    }
+    caravan.status.clone()
+}
+
+#[test]
+fn path_connects_cities_step_by_step() {
+    let path = plan_path((0, 0), (20, 5));
+
+    assert_eq!(path.first(), Some(&(0, 0)));
+    assert_eq!(path.last(), Some(&(20, 5)));
+    assert_eq!(path.len(), 21);
+    for step in path.windows(2) {
+        assert!((step[0].0 - step[1].0).abs() <= 1);
+        assert!((step[0].1 - step[1].1).abs() <= 1);
+    }
+}
+
+#[test]
+fn caravan_holds_cargo_and_payment() {
+    let cities = cities();
+    let caravan = caravan(&cities, 2);
+
+    assert_eq!(caravan.quantity, 10);
+    assert_eq!(caravan.payment, 30);
+    assert_eq!(caravan.escort, 2);
+    assert_eq!(caravan.remaining_steps(), 20);
+}
+
+#[test]
+fn escort_and_hostile_buildings_change_risk() {
+    let mut cities = cities();
+    let world = WorldMap::new(64, 64);
+    let unescorted = caravan(&cities, 0).step_risk(&world, &cities);
+    let escorted = caravan(&cities, 10).step_risk(&world, &cities);
+    assert!(escorted < unescorted, "{} < {}", escorted, unescorted);
+
+    let mut hostile = city("Крепость", "bob", (2, 2));
+    place(&mut hostile, BuildingType::Barracks, (2, 2));
+    cities.insert(hostile.name.clone(), hostile);
+    let threatened = caravan(&cities, 0).step_risk(&world, &cities);
+    assert!(threatened > unescorted, "{} > {}", threatened, unescorted);
+}
+
+#[test]
+fn ambushes_are_reproducible_with_seed() {
+    let mut cities = cities();
+    let mut hostile = city("Крепость", "bob", (10, 3));
+    place(&mut hostile, BuildingType::Barracks, (10, 3));
+    hostile.buildings.values_mut().for_each(|b| b.level = 10);
+    cities.insert(hostile.name.clone(), hostile);
+
+    for seed in 0..20 {
+        let mut first = caravan(&cities, 0);
+        let mut second = caravan(&cities, 0);
+        assert_eq!(
+            run(&mut first, &cities, seed),
+            run(&mut second, &cities, seed)
+        );
+        assert_eq!(first.progress, second.progress);
+    }
+}
+
+#[test]
+fn caravan_arrives_on_safe_road() {
+    let cities = cities();
+    let mut caravan = caravan(&cities, 50);
+    assert_eq!(run(&mut caravan, &cities, 7), CaravanStatus::Arrived);
+    assert_eq!(caravan.position(), (20, 5));
+}
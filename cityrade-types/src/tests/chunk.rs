@@ -0,0 +1,67 @@
+use crate::chunk::{CHUNK_SIZE, Chunk, chunk_position};
+use crate::world::{TerrainTile, WorldMap};
+
+#[test]
+fn chunk_positions_cover_negative_coordinates() {
+    assert_eq!(chunk_position(0, 0), (0, 0));
+    assert_eq!(chunk_position(CHUNK_SIZE - 1, CHUNK_SIZE), (0, 1));
+    assert_eq!(chunk_position(-1, -CHUNK_SIZE), (-1, -1));
+    assert_eq!(chunk_position(-CHUNK_SIZE - 1, 0), (-2, 0));
+}
+
+#[test]
+fn chunk_stores_tiles_by_world_coordinates() {
+    let mut chunk = Chunk::filled(&TerrainTile::Land);
+    chunk.set(-1, -1, &TerrainTile::Water);
+    chunk.set(CHUNK_SIZE + 2, 3, &TerrainTile::Forest);
+
+    assert_eq!(chunk.get(-1, -1), &TerrainTile::Water);
+    assert_eq!(chunk.get(2, 3), &TerrainTile::Forest);
+    assert_eq!(chunk.get(0, 0), &TerrainTile::Land);
+}
+
+#[test]
+fn chunks_are_created_on_first_write() {
+    let mut world = WorldMap::new(4096, 4096);
+    assert_eq!(world.loaded_chunks(), 0);
+    assert_eq!(world.get_tile(4000, 4000), Some(&TerrainTile::Land));
+    assert_eq!(world.get_tile(4096, 0), None);
+
+    world.set_tile(4000, 4000, TerrainTile::Mountain);
+    assert_eq!(world.loaded_chunks(), 1);
+    assert_eq!(world.get_tile(4000, 4000), Some(&TerrainTile::Mountain));
+}
+
+#[test]
+fn buildings_and_cities_overlay_terrain() {
+    let mut world = WorldMap::new(64, 64);
+    world.set_tile(5, 5, TerrainTile::Forest);
+    world.add_city(5, 5, "Новгород".to_string());
+    assert_eq!(
+        world.get_tile(5, 5),
+        Some(&TerrainTile::City("Новгород".to_string()))
+    );
+
+    world.set_tile(5, 5, TerrainTile::Desert);
+    assert_eq!(world.get_tile(5, 5), Some(&TerrainTile::Desert));
+}
+
+#[test]
+fn chunk_round_trips_through_save() {
+    let mut chunk = Chunk::filled(&TerrainTile::Land);
+    chunk.set(5, 7, &TerrainTile::River);
+
+    let saved = ron::to_string(&chunk).unwrap();
+    let loaded: Chunk = ron::from_str(&saved).unwrap();
+
+    assert_eq!(loaded, chunk);
+}
+
+#[test]
+fn chunk_with_wrong_size_is_rejected() {
+    let error = ron::from_str::<Chunk>("(tiles: [0, 1, 2])").unwrap_err();
+    assert!(error.to_string().contains("клеток"), "{}", error);
+
+    let tiles = vec!["0"; (CHUNK_SIZE * CHUNK_SIZE) as usize + 1].join(", ");
+    assert!(ron::from_str::<Chunk>(&format!("(tiles: [{}])", tiles)).is_err());
+}
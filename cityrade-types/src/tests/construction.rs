@@ -0,0 +1,120 @@
+use super::city;
+use crate::building::BuildingType;
+use crate::city::City;
+use crate::construction::DEFAULT_BUILD_SLOTS;
+use crate::game::{Command, Game};
+use crate::ledger::{LedgerEntry, LedgerReason};
+use crate::resources::ResourceType;
+use crate::technology::TechnologyType;
+
+// Город, которому хватает ресурсов на несколько ферм
+fn farming_city() -> City {
+    let mut city = city("Новгород", "alice", (0, 0));
+    city.research.completed.insert(TechnologyType::Agriculture);
+    city.resources
+        .apply(
+            LedgerEntry::new(LedgerReason::Adjustment)
+                .credit(ResourceType::Wood, 500)
+                .credit(ResourceType::Gold, 500),
+        )
+        .unwrap();
+    city
+}
+
+#[test]
+fn only_first_slots_make_progress() {
+    let mut city = farming_city();
+    let projects: Vec<String> = (0..DEFAULT_BUILD_SLOTS + 1)
+        .map(|i| {
+            city.add_building(BuildingType::Farm, format!("Ферма {}", i), (i as i32, 1))
+                .unwrap();
+            city.construction.projects.last().unwrap().id.clone()
+        })
+        .collect();
+
+    city.update_construction();
+
+    for (i, id) in projects.iter().enumerate() {
+        let progress = city.construction.get(id).unwrap().progress;
+        assert_eq!(progress, (i < DEFAULT_BUILD_SLOTS) as u32);
+    }
+
+    // Когда первые проекты завершены, освободившаяся бригада берется за следующий
+    let build_time = BuildingType::Farm.build_time(1);
+    let mut finished = Vec::new();
+    for _ in 1..build_time {
+        finished.extend(city.update_construction());
+    }
+    assert_eq!(finished.len(), DEFAULT_BUILD_SLOTS);
+    assert_eq!(city.buildings.len(), DEFAULT_BUILD_SLOTS);
+
+    city.update_construction();
+    assert_eq!(city.construction.projects[0].progress, 1);
+}
+
+#[test]
+fn cancel_before_start_refunds_everything() {
+    let mut city = farming_city();
+    let wood = city.resources.get(&ResourceType::Wood);
+    let gold = city.resources.get(&ResourceType::Gold);
+
+    city.add_building(BuildingType::Farm, "Ферма".to_string(), (1, 1))
+        .unwrap();
+    let id = city.construction.projects[0].id.clone();
+    let refund = city.cancel_construction(&id).unwrap();
+
+    assert!(refund.contains(&(ResourceType::Wood, 30)));
+    assert_eq!(city.resources.get(&ResourceType::Wood), wood);
+    assert_eq!(city.resources.get(&ResourceType::Gold), gold);
+    assert!(city.construction.projects.is_empty());
+}
+
+#[test]
+fn cancel_after_start_refunds_half() {
+    let mut city = farming_city();
+    let wood = city.resources.get(&ResourceType::Wood);
+    let gold = city.resources.get(&ResourceType::Gold);
+
+    city.add_building(BuildingType::Farm, "Ферма".to_string(), (1, 1))
+        .unwrap();
+    let id = city.construction.projects[0].id.clone();
+    city.update_construction();
+    city.cancel_construction(&id).unwrap();
+
+    assert_eq!(city.resources.get(&ResourceType::Wood), wood - 15);
+    assert_eq!(city.resources.get(&ResourceType::Gold), gold - 10);
+    assert!(city.cancel_construction(&id).is_err());
+}
+
+#[test]
+fn game_builds_only_in_owned_city() {
+    let mut game = Game::new(1);
+    game.add_city(farming_city()).unwrap();
+    let build = Command::Build {
+        city: "Новгород".to_string(),
+        building_type: BuildingType::Farm,
+        name: "Ферма".to_string(),
+        position: (1, 1),
+    };
+
+    assert!(game.execute("bob", build.clone()).is_err());
+    game.execute("alice", build.clone()).unwrap();
+    // Позиция уже занята стройкой
+    assert!(game.execute("alice", build).is_err());
+
+    let city = &game.cities["Новгород"];
+    assert_eq!(city.construction.projects.len(), 1);
+    let project_id = city.construction.projects[0].id.clone();
+    let wood = city.resources.get(&ResourceType::Wood);
+
+    let cancel = Command::CancelConstruction {
+        city: "Новгород".to_string(),
+        project_id,
+    };
+    assert!(game.execute("bob", cancel.clone()).is_err());
+    game.execute("alice", cancel).unwrap();
+    assert_eq!(
+        game.cities["Новгород"].resources.get(&ResourceType::Wood),
+        wood + 30
+    );
+}
@@ -0,0 +1,179 @@
+use crate::content::{self, ContentWatcher, GameContent};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+// Пустой каталог контента во временной папке
+fn content_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cityrade-content-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Записывает файл с заданным временем изменения
+fn write(dir: &Path, name: &str, source: &str, modified: SystemTime) {
+    let path = dir.join(name);
+    std::fs::write(&path, source).unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+}
+
+// Ошибка загрузки каталога с единственным файлом
+fn load_error(name: &str, source: &str) -> String {
+    let dir = content_dir();
+    write(&dir, name, source, SystemTime::now());
+    let error = GameContent::load_dir(&dir).unwrap_err();
+    std::fs::remove_dir_all(&dir).unwrap();
+    error
+}
+
+#[test]
+fn mod_file_overrides_base_definitions() {
+    let dir = content_dir();
+    write(
+        &dir,
+        "prices.toml",
+        "market_prices = [[\"Food\", 15]]\n",
+        SystemTime::now(),
+    );
+
+    let content = GameContent::load_dir(&dir).unwrap();
+    assert_eq!(
+        content.market_prices[&crate::resources::ResourceType::Food],
+        15
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn malformed_files_are_rejected() {
+    let error = load_error("broken.ron", "(buildings: [(building_type: Farm,");
+    assert!(error.contains("broken.ron"), "{}", error);
+
+    let error = load_error(
+        "types.toml",
+        "[[terrains]]\nterrain = \"Plain\"\ndisplay_name = 42\n",
+    );
+    assert!(error.contains("types.toml"), "{}", error);
+}
+
+#[test]
+fn out_of_range_values_are_rejected() {
+    let error = load_error(
+        "farm.ron",
+        r#"(buildings: [(
+            building_type: Farm,
+            display_name: "Ферма",
+            description: "",
+            base_cost: [],
+            decay_rate: 2.0,
+        )])"#,
+    );
+    assert!(error.contains("decay_rate"), "{}", error);
+
+    let error = load_error("prices.toml", "market_prices = [[\"Gold\", 0]]\n");
+    assert!(error.contains("Gold"), "{}", error);
+
+    let error = load_error(
+        "recipes.ron",
+        "(recipes: [(output: Bread, quantity: 0, inputs: [], duration: 1)])",
+    );
+    assert!(error.contains("Bread"), "{}", error);
+}
+
+#[test]
+fn unknown_buildings_are_rejected() {
+    let error = load_error(
+        "adjacency.ron",
+        r#"(adjacency: [(
+            building: Farm,
+            neighbor: Custom("Мельница"),
+            effect: Happiness(0.1),
+        )])"#,
+    );
+    assert!(error.contains("Мельница"), "{}", error);
+
+    let error = load_error(
+        "tech.ron",
+        r#"(technologies: [(
+            tech_type: Agriculture,
+            name: "Сельское хозяйство",
+            description: "",
+            cost: 100,
+            prerequisites: [],
+            unlock_effects: [UnlockBuilding(Custom("Мельница"))],
+        )])"#,
+    );
+    assert!(error.contains("Мельница"), "{}", error);
+}
+
+#[test]
+fn unknown_goods_and_technologies_are_rejected() {
+    let error = load_error(
+        "goods.ron",
+        r#"(goods: [(good: Gems, display_name: "", description: "", base_price: 5)])"#,
+    );
+    assert!(error.contains("Gems"), "{}", error);
+
+    let error = load_error(
+        "recipes.ron",
+        "(recipes: [(output: Gems, quantity: 1, inputs: [], duration: 1)])",
+    );
+    assert!(error.contains("Gems"), "{}", error);
+
+    let error = load_error(
+        "tech.ron",
+        r#"(technologies: [(
+            tech_type: Agriculture,
+            name: "Сельское хозяйство",
+            description: "",
+            cost: 100,
+            prerequisites: [Alchemy],
+            unlock_effects: [],
+        )])"#,
+    );
+    assert!(error.contains("Alchemy"), "{}", error);
+}
+
+#[test]
+fn failed_reload_keeps_previous_content() {
+    let dir = content_dir();
+    // Пустой файл дает тот же контент, что и базовый, и не мешает другим тестам
+    write(&dir, "mod.ron", "()", SystemTime::now());
+    let mut watcher = ContentWatcher::new(&dir);
+    assert!(watcher.poll().unwrap());
+    assert!(!watcher.poll().unwrap());
+    let installed = content::current();
+
+    write(
+        &dir,
+        "mod.ron",
+        "(market_prices: [(Gold, 0)])",
+        SystemTime::now(),
+    );
+    assert!(watcher.poll().is_err());
+    assert!(Arc::ptr_eq(&installed, &content::current()));
+
+    // Сломанный файл не перечитывается, пока его снова не изменят
+    assert!(!watcher.poll().unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn edits_within_one_second_are_detected() {
+    let dir = content_dir();
+    let saved = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    write(&dir, "mod.ron", "() ", saved);
+    let mut watcher = ContentWatcher::new(&dir);
+    assert!(watcher.poll().unwrap());
+
+    // Та же длина и то же время изменения, другое содержимое
+    write(&dir, "mod.ron", " ()", saved);
+    assert!(watcher.poll().unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,76 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::item::GoodType;
+use crate::resources::ResourceType;
+
+#[test]
+fn crafting_consumes_recipe_inputs_up_front() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    place(&mut city, BuildingType::Workshop, (1, 1));
+    let wood = city.resources.get(&ResourceType::Wood);
+
+    city.craft(GoodType::Planks, 2).unwrap();
+
+    assert_eq!(city.resources.get(&ResourceType::Wood), wood - 20);
+    assert_eq!(city.crafting[0].quantity, 10);
+    assert_eq!(city.crafting[0].total_ticks, 6);
+}
+
+#[test]
+fn crafting_without_inputs_changes_nothing() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    assert!(city.craft(GoodType::Planks, 1).is_err());
+
+    place(&mut city, BuildingType::Workshop, (1, 1));
+    let wood = city.resources.get(&ResourceType::Wood);
+
+    // Для инструментов нужно железо, которого у города нет
+    assert!(city.craft(GoodType::Tools, 1).is_err());
+    assert!(city.craft(GoodType::Planks, 0).is_err());
+    assert_eq!(city.resources.get(&ResourceType::Wood), wood);
+    assert!(city.crafting.is_empty());
+}
+
+#[test]
+fn finished_goods_go_to_inventory() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    place(&mut city, BuildingType::Workshop, (1, 1));
+    city.craft(GoodType::Planks, 1).unwrap();
+
+    for _ in 1..GoodType::Planks.recipe().unwrap().duration {
+        city.update_crafting();
+    }
+    assert_eq!(city.inventory.get(&GoodType::Planks), 0);
+
+    city.update_crafting();
+    assert_eq!(city.inventory.get(&GoodType::Planks), 5);
+    assert!(city.crafting.is_empty());
+}
+
+#[test]
+fn workshop_level_limits_parallel_orders() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let workshop = place(&mut city, BuildingType::Workshop, (1, 1));
+    city.craft(GoodType::Planks, 1).unwrap();
+    city.craft(GoodType::Planks, 1).unwrap();
+    assert_eq!(city.crafting_capacity(), 1);
+
+    city.update_crafting();
+    assert_eq!(city.crafting[0].progress, 1);
+    assert_eq!(city.crafting[1].progress, 0);
+
+    city.buildings.get_mut(&workshop).unwrap().level = 2;
+    assert_eq!(city.crafting_capacity(), 2);
+    city.update_crafting();
+    assert_eq!(city.crafting[0].progress, 2);
+    assert_eq!(city.crafting[1].progress, 1);
+}
+
+#[test]
+fn worn_out_workshop_loses_capacity() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let workshop = place(&mut city, BuildingType::Workshop, (1, 1));
+    city.buildings.get_mut(&workshop).unwrap().condition = 0.0;
+
+    assert_eq!(city.crafting_capacity(), 0);
+}
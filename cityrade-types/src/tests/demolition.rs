@@ -0,0 +1,82 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::item::GoodType;
+use crate::ledger::{LedgerEntry, LedgerReason};
+use crate::population::PopulationClass;
+use crate::resources::ResourceType;
+use crate::technology::TechnologyType;
+
+#[test]
+fn demolition_returns_part_of_investment() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    city.research.completed.insert(TechnologyType::Agriculture);
+    let id = city
+        .add_building(BuildingType::Farm, "Ферма".to_string(), (1, 1))
+        .unwrap();
+    for _ in 0..BuildingType::Farm.build_time(1) {
+        city.update_construction();
+    }
+    assert_eq!(
+        city.buildings[&id].invested,
+        [(ResourceType::Wood, 30), (ResourceType::Gold, 20)]
+    );
+    assert_eq!(
+        city.buildings[&id].salvage(),
+        [(ResourceType::Wood, 15), (ResourceType::Gold, 10)]
+    );
+
+    let wood = city.resources.get(&ResourceType::Wood);
+    let gold = city.resources.get(&ResourceType::Gold);
+    city.demolish_building(&id).unwrap();
+    assert!(city.demolish_building(&id).is_err());
+    for _ in 0..BuildingType::Farm.demolition_time(1) {
+        city.update_construction();
+    }
+
+    assert!(!city.buildings.contains_key(&id));
+    assert_eq!(city.resources.get(&ResourceType::Wood), wood + 15);
+    assert_eq!(city.resources.get(&ResourceType::Gold), gold + 10);
+}
+
+#[test]
+fn barracks_training_soldiers_cannot_be_demolished() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let barracks = place(&mut city, BuildingType::Barracks, (1, 1));
+    city.resources
+        .apply(LedgerEntry::new(LedgerReason::Adjustment).credit(ResourceType::Iron, 10))
+        .unwrap();
+    city.train_citizens(PopulationClass::Peasant, PopulationClass::Soldier, 1)
+        .unwrap();
+
+    assert!(city.demolish_building(&barracks).is_err());
+
+    // Вторые казармы продолжат обучение
+    place(&mut city, BuildingType::Barracks, (2, 1));
+    city.demolish_building(&barracks).unwrap();
+}
+
+#[test]
+fn workshop_with_orders_cannot_be_demolished() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let workshop = place(&mut city, BuildingType::Workshop, (1, 1));
+    city.craft(GoodType::Planks, 1).unwrap();
+
+    let error = city.demolish_building(&workshop).unwrap_err();
+    assert!(error.contains("мастерской"));
+
+    city.crafting.clear();
+    city.demolish_building(&workshop).unwrap();
+}
+
+#[test]
+fn laboratory_cannot_be_demolished_during_research() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let laboratory = place(&mut city, BuildingType::Laboratory, (1, 1));
+    city.start_research(TechnologyType::Agriculture).unwrap();
+
+    let error = city.demolish_building(&laboratory).unwrap_err();
+    assert!(error.contains("исследования"));
+
+    city.research.queue.clear();
+    city.demolish_building(&laboratory).unwrap();
+}
@@ -0,0 +1,114 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::game::{Command, Game, GameEvent};
+use crate::market::ContractStatus;
+use crate::resources::ResourceType;
+use crate::technology::TechnologyType;
+
+// Мир с двумя городами разных игроков
+fn setup() -> Game {
+    let mut game = Game::new(7);
+    let mut source = city("Новгород", "alice", (0, 0));
+    source.research.completed.insert(TechnologyType::Trade);
+    game.add_city(source).unwrap();
+    game.add_city(city("Псков", "bob", (4, 0))).unwrap();
+    game
+}
+
+#[test]
+fn duplicate_city_is_rejected() {
+    let mut game = setup();
+    assert!(game.add_city(city("Псков", "carol", (8, 8))).is_err());
+}
+
+#[test]
+fn commands_drive_contract_to_completion() {
+    let mut game = setup();
+    let route_id = game
+        .execute(
+            "alice",
+            Command::EstablishTradeRoute {
+                source_city: "Новгород".to_string(),
+                target_city: "Псков".to_string(),
+                resource: ResourceType::Wood,
+                quantity: 2,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        game.trade.get_route(&route_id).unwrap().status,
+        ContractStatus::Proposed
+    );
+
+    let accept = Command::AcceptTradeRoute {
+        route_id: route_id.clone(),
+    };
+    assert!(game.execute("alice", accept.clone()).is_err());
+    game.execute("bob", accept).unwrap();
+
+    let mut events = Vec::new();
+    for _ in 0..20 {
+        events.extend(game.tick());
+    }
+    assert_eq!(game.tick, 20);
+    assert!(!events.is_empty());
+    assert!(
+        events
+            .iter()
+            .all(|event| matches!(event, GameEvent::Caravan(_)))
+    );
+    // Завершенный контракт убирается из списка маршрутов
+    assert!(game.trade.get_route(&route_id).is_none());
+}
+
+#[test]
+fn world_is_generated_from_game_seed() {
+    let tiles = |game: &Game| {
+        (0..32)
+            .flat_map(|x| (0..32).map(move |y| (x, y)))
+            .map(|(x, y)| game.world.get_tile(x, y).cloned())
+            .collect::<Vec<_>>()
+    };
+    let first = Game::new(7);
+
+    assert_eq!(tiles(&first), tiles(&Game::new(7)));
+    assert_ne!(tiles(&first), tiles(&Game::new(8)));
+    assert!(!first.world.deposits_near((128, 128), 128).is_empty());
+}
+
+#[test]
+fn founded_city_claims_nearby_deposits() {
+    let mut game = Game::new(7);
+    let deposit = game
+        .world
+        .deposits_near((128, 128), 128)
+        .into_iter()
+        .find(|deposit| deposit.resource == ResourceType::Iron)
+        .cloned()
+        .unwrap();
+
+    let mut founded = city("Рудный", "alice", deposit.position);
+    place(&mut founded, BuildingType::Mine, (0, 0));
+    let mut unclaimed = founded.clone();
+    game.add_city(founded).unwrap();
+
+    let founded = game.cities.get_mut("Рудный").unwrap();
+    assert!(
+        founded
+            .deposits
+            .iter()
+            .any(|d| d.position == deposit.position)
+    );
+    assert!(
+        game.world
+            .get_deposit(deposit.position.0, deposit.position.1)
+            .is_none()
+    );
+
+    founded.update_resource_production();
+    unclaimed.update_resource_production();
+    assert!(
+        founded.resources.get_production_rate(&ResourceType::Iron)
+            > unclaimed.resources.get_production_rate(&ResourceType::Iron)
+    );
+}
@@ -0,0 +1,54 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::labor::assign_workers;
+use crate::population::PopulationClass;
+
+#[test]
+fn higher_priority_buildings_are_staffed_first() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let first = place(&mut city, BuildingType::Farm, (1, 0));
+    let second = place(&mut city, BuildingType::Farm, (2, 0));
+    city.population.classes.insert(PopulationClass::Peasant, 7);
+
+    city.set_staffing_priority(&second, 5).unwrap();
+    let staffing = assign_workers(&city.population, &city.buildings, &city.labor_priorities);
+
+    assert_eq!(staffing.ratio(&second), 1.0);
+    assert_eq!(staffing.ratio(&first), 2.0 / 5.0);
+    assert_eq!(staffing.assigned[&first], [(PopulationClass::Peasant, 2)]);
+    assert!(!staffing.unemployed.contains_key(&PopulationClass::Peasant));
+}
+
+#[test]
+fn equal_priorities_are_staffed_by_id() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let first = place(&mut city, BuildingType::Farm, (1, 0));
+    let second = place(&mut city, BuildingType::Farm, (2, 0));
+    city.population.classes.insert(PopulationClass::Peasant, 3);
+
+    let staffing = assign_workers(&city.population, &city.buildings, &city.labor_priorities);
+
+    assert_eq!(staffing.ratio(&first), 3.0 / 5.0);
+    assert_eq!(staffing.ratio(&second), 0.0);
+}
+
+#[test]
+fn surplus_workers_are_unemployed() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let farm = place(&mut city, BuildingType::Farm, (1, 0));
+    city.population.classes.insert(PopulationClass::Peasant, 8);
+
+    let staffing = assign_workers(&city.population, &city.buildings, &city.labor_priorities);
+
+    assert_eq!(staffing.ratio(&farm), 1.0);
+    assert_eq!(staffing.unemployed[&PopulationClass::Peasant], 3);
+    // Солдаты и знать не ищут работу
+    assert!(!staffing.unemployed.contains_key(&PopulationClass::Soldier));
+    assert!(!staffing.unemployed.contains_key(&PopulationClass::Noble));
+}
+
+#[test]
+fn priority_requires_existing_building() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    assert!(city.set_staffing_priority("missing", 3).is_err());
+}
@@ -0,0 +1,130 @@
+use super::{city, place};
+use crate::building::{Building, BuildingType, MIN_CONDITION_EFFICIENCY, POOR_CONDITION};
+use crate::ledger::LedgerReason;
+use crate::resources::ResourceType;
+
+fn farm() -> Building {
+    Building::new(
+        "farm".to_string(),
+        "Ферма".to_string(),
+        BuildingType::Farm,
+        (0, 0),
+    )
+}
+
+#[test]
+fn maintenance_scales_with_level_and_saturates() {
+    assert_eq!(
+        BuildingType::Market.maintenance(3),
+        [(ResourceType::Gold, 6)]
+    );
+    assert_eq!(
+        BuildingType::Market.maintenance(u32::MAX),
+        [(ResourceType::Gold, u32::MAX)]
+    );
+}
+
+#[test]
+fn unmaintained_buildings_decay_twice_as_fast() {
+    let rate = BuildingType::Farm.decay_rate();
+    let mut maintained = farm();
+    let mut neglected = farm();
+
+    maintained.decay(true);
+    neglected.decay(false);
+
+    assert!((maintained.condition - (1.0 - rate)).abs() < 1e-6);
+    assert!((neglected.condition - (1.0 - 2.0 * rate)).abs() < 1e-6);
+
+    // Состояние не опускается ниже нуля
+    neglected.condition = rate / 2.0;
+    neglected.decay(false);
+    assert_eq!(neglected.condition, 0.0);
+}
+
+#[test]
+fn city_maintenance_pays_upkeep_and_decays() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let id = place(&mut city, BuildingType::Farm, (1, 1));
+    let rate = BuildingType::Farm.decay_rate();
+    let wood = city.resources.get(&ResourceType::Wood);
+
+    city.update_maintenance();
+    assert_eq!(city.resources.get(&ResourceType::Wood), wood - 1);
+    assert!((city.buildings[&id].condition - (1.0 - rate)).abs() < 1e-6);
+
+    // Без дерева ферма не обслуживается и изнашивается быстрее
+    assert!(
+        city.resources
+            .pay(LedgerReason::Adjustment, &[(ResourceType::Wood, wood - 1)])
+    );
+    city.update_maintenance();
+    assert!((city.buildings[&id].condition - (1.0 - 3.0 * rate)).abs() < 1e-6);
+}
+
+#[test]
+fn city_maintenance_records_one_ledger_entry_per_tick() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    for x in 0..5 {
+        place(&mut city, BuildingType::Farm, (x, 2));
+    }
+    let wood = city.resources.get(&ResourceType::Wood);
+    let entries = city.resources.ledger().entries.len();
+
+    city.update_maintenance();
+    assert_eq!(city.resources.get(&ResourceType::Wood), wood - 5);
+    assert_eq!(city.resources.ledger().entries.len(), entries + 1);
+
+    let entry = city.resources.ledger().recent(1).next().unwrap();
+    assert_eq!(entry.reason, LedgerReason::Maintenance);
+    assert_eq!(entry.deltas, [(ResourceType::Wood, -5)]);
+}
+
+#[test]
+fn condition_efficiency_drops_below_poor_condition() {
+    let mut building = farm();
+    assert_eq!(building.condition_efficiency(), 1.0);
+
+    building.condition = POOR_CONDITION;
+    assert_eq!(building.condition_efficiency(), 1.0);
+
+    building.condition = POOR_CONDITION / 2.0;
+    let halfway = (1.0 + MIN_CONDITION_EFFICIENCY) / 2.0;
+    assert!((building.condition_efficiency() - halfway).abs() < 1e-6);
+
+    building.condition = 0.0;
+    assert_eq!(building.condition_efficiency(), MIN_CONDITION_EFFICIENCY);
+}
+
+#[test]
+fn repair_cost_grows_with_damage_and_level() {
+    let mut building = farm();
+    assert!(building.repair_cost().is_empty());
+
+    building.condition = 0.5;
+    assert_eq!(
+        building.repair_cost(),
+        [(ResourceType::Wood, 5), (ResourceType::Gold, 3)]
+    );
+
+    building.level = 2;
+    assert_eq!(
+        building.repair_cost(),
+        [(ResourceType::Wood, 9), (ResourceType::Gold, 6)]
+    );
+}
+
+#[test]
+fn repair_restores_condition_for_a_price() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let id = place(&mut city, BuildingType::Farm, (1, 1));
+    assert!(city.repair_building(&id).is_err());
+
+    city.buildings.get_mut(&id).unwrap().condition = 0.5;
+    let wood = city.resources.get(&ResourceType::Wood);
+    let costs = city.repair_building(&id).unwrap();
+
+    assert_eq!(costs, [(ResourceType::Wood, 5), (ResourceType::Gold, 3)]);
+    assert_eq!(city.resources.get(&ResourceType::Wood), wood - 5);
+    assert_eq!(city.buildings[&id].condition, 1.0);
+}
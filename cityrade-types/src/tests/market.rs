@@ -0,0 +1,241 @@
+use super::city;
+use crate::city::City;
+use crate::market::{ContractStatus, TradeManager};
+use crate::population::PopulationClass;
+use crate::resources::ResourceType;
+use crate::technology::TechnologyType;
+use crate::world::WorldMap;
+use rand::{SeedableRng, rngs::StdRng};
+use std::collections::HashMap;
+
+// Два торгующих города: Новгород (alice) и Псков (bob или alice)
+fn setup(target_owner: &str) -> (TradeManager, HashMap<String, City>) {
+    let mut source = city("Новгород", "alice", (0, 0));
+    source.research.completed.insert(TechnologyType::Trade);
+    let target = city("Псков", target_owner, (4, 0));
+
+    let mut trade = TradeManager::new();
+    trade.create_city_market("Новгород");
+    trade.create_city_market("Псков");
+    let cities = [source, target]
+        .into_iter()
+        .map(|city| (city.name.clone(), city))
+        .collect();
+    (trade, cities)
+}
+
+fn propose(trade: &mut TradeManager, cities: &HashMap<String, City>) -> String {
+    trade
+        .establish_trade_route(cities, "alice", "Новгород", "Псков", ResourceType::Wood, 2)
+        .unwrap()
+}
+
+// Тикает торговлю, пока контракт не завершится
+fn settle(trade: &mut TradeManager, cities: &mut HashMap<String, City>, route_id: &str) {
+    let world = WorldMap::new(16, 16);
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..20 {
+        trade.update_trade_routes(cities, &world, &mut rng);
+        if trade.get_route(route_id).unwrap().is_finished() {
+            return;
+        }
+    }
+    panic!("контракт не завершился");
+}
+
+fn gold(cities: &HashMap<String, City>, name: &str) -> u32 {
+    cities[name].resources.get(&ResourceType::Gold)
+}
+
+#[test]
+fn contract_between_own_cities_is_accepted_at_once() {
+    let (mut trade, cities) = setup("alice");
+    let route_id = propose(&mut trade, &cities);
+    assert_eq!(
+        trade.get_route(&route_id).unwrap().status,
+        ContractStatus::Accepted
+    );
+}
+
+#[test]
+fn only_owner_of_source_city_proposes_contracts() {
+    let (mut trade, cities) = setup("bob");
+    let result =
+        trade.establish_trade_route(&cities, "bob", "Новгород", "Псков", ResourceType::Wood, 10);
+    assert!(result.is_err());
+}
+
+#[test]
+fn trade_routes_require_technology() {
+    let (mut trade, mut cities) = setup("alice");
+    cities
+        .get_mut("Новгород")
+        .unwrap()
+        .research
+        .completed
+        .clear();
+    let result = trade.establish_trade_route(
+        &cities,
+        "alice",
+        "Новгород",
+        "Псков",
+        ResourceType::Wood,
+        10,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn contract_waits_for_the_buyer_decision() {
+    let (mut trade, cities) = setup("bob");
+    let route_id = propose(&mut trade, &cities);
+    assert_eq!(
+        trade.get_route(&route_id).unwrap().status,
+        ContractStatus::Proposed
+    );
+
+    assert!(
+        trade
+            .accept_trade_route(&cities, "alice", &route_id)
+            .is_err()
+    );
+    trade.accept_trade_route(&cities, "bob", &route_id).unwrap();
+    assert_eq!(
+        trade.get_route(&route_id).unwrap().status,
+        ContractStatus::Accepted
+    );
+    assert!(trade.reject_trade_route(&cities, "bob", &route_id).is_err());
+}
+
+#[test]
+fn rejected_contract_never_ships() {
+    let (mut trade, mut cities) = setup("bob");
+    let route_id = propose(&mut trade, &cities);
+    trade.reject_trade_route(&cities, "bob", &route_id).unwrap();
+
+    trade.update_trade_routes(
+        &mut cities,
+        &WorldMap::new(16, 16),
+        &mut StdRng::seed_from_u64(1),
+    );
+    assert!(trade.caravans.is_empty());
+    assert_eq!(cities["Новгород"].resources.get(&ResourceType::Wood), 100);
+
+    trade.clear_finished_routes();
+    assert!(trade.get_route(&route_id).is_none());
+}
+
+#[test]
+fn contract_settles_at_the_agreed_price() {
+    let (mut trade, mut cities) = setup("bob");
+    let route_id = propose(&mut trade, &cities);
+    let price = trade.get_route(&route_id).unwrap().total_price();
+    trade.accept_trade_route(&cities, "bob", &route_id).unwrap();
+
+    // Цена на рынке после согласования не влияет на сделку
+    let market = trade.markets.get_mut("Новгород").unwrap();
+    market.demand_factors.insert(ResourceType::Wood, 5.0);
+    market.update_prices();
+
+    let (source_gold, target_gold) = (gold(&cities, "Новгород"), gold(&cities, "Псков"));
+    settle(&mut trade, &mut cities, &route_id);
+
+    assert_eq!(
+        trade.get_route(&route_id).unwrap().status,
+        ContractStatus::Completed
+    );
+    assert_eq!(gold(&cities, "Новгород"), source_gold + price);
+    assert_eq!(gold(&cities, "Псков"), target_gold - price);
+    assert_eq!(cities["Новгород"].resources.get(&ResourceType::Wood), 98);
+    assert_eq!(cities["Псков"].resources.get(&ResourceType::Wood), 102);
+}
+
+#[test]
+fn contract_fails_when_buyer_cannot_pay() {
+    let (mut trade, mut cities) = setup("alice");
+    let route_id = propose(&mut trade, &cities);
+    cities.get_mut("Псков").unwrap().resources =
+        crate::resources::Resources::with_values(HashMap::from([(ResourceType::Gold, 0)]));
+
+    trade.update_trade_routes(
+        &mut cities,
+        &WorldMap::new(16, 16),
+        &mut StdRng::seed_from_u64(1),
+    );
+
+    assert_eq!(
+        trade.get_route(&route_id).unwrap().status,
+        ContractStatus::Failed
+    );
+    assert_eq!(cities["Новгород"].resources.get(&ResourceType::Wood), 100);
+}
+
+#[test]
+fn escort_is_set_by_source_owner_from_garrison() {
+    let (mut trade, cities) = setup("bob");
+    let route_id = propose(&mut trade, &cities);
+    let soldiers = cities["Новгород"]
+        .population
+        .count(&PopulationClass::Soldier);
+
+    assert!(
+        trade
+            .set_route_escort(&cities, "bob", &route_id, 1)
+            .is_err()
+    );
+    assert!(
+        trade
+            .set_route_escort(&cities, "alice", &route_id, soldiers + 1)
+            .is_err()
+    );
+    trade
+        .set_route_escort(&cities, "alice", &route_id, soldiers)
+        .unwrap();
+    assert_eq!(trade.get_route(&route_id).unwrap().escort, soldiers);
+}
+
+#[test]
+fn escort_leaves_and_returns_with_the_caravan() {
+    let (mut trade, mut cities) = setup("alice");
+    let route_id = propose(&mut trade, &cities);
+    trade
+        .set_route_escort(&cities, "alice", &route_id, 2)
+        .unwrap();
+    let soldiers = cities["Новгород"]
+        .population
+        .count(&PopulationClass::Soldier);
+
+    trade.update_trade_routes(
+        &mut cities,
+        &WorldMap::new(16, 16),
+        &mut StdRng::seed_from_u64(1),
+    );
+    assert_eq!(
+        cities["Новгород"]
+            .population
+            .count(&PopulationClass::Soldier),
+        soldiers - 2
+    );
+
+    settle(&mut trade, &mut cities, &route_id);
+    assert_eq!(
+        cities["Новгород"]
+            .population
+            .count(&PopulationClass::Soldier),
+        soldiers
+    );
+}
+
+#[test]
+fn unpriced_resource_is_rejected_without_panic() {
+    let (mut trade, cities) = setup("alice");
+    let result = trade.establish_trade_route(
+        &cities,
+        "alice",
+        "Новгород",
+        "Псков",
+        ResourceType::Food,
+        10,
+    );
+    assert!(result.unwrap_err().contains("Еда"));
+}
@@ -0,0 +1,133 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::city::City;
+use crate::ledger::{LedgerEntry, LedgerReason};
+use crate::mobility::{ConversionRequirement, ConversionRule};
+use crate::population::PopulationClass;
+use crate::resources::ResourceType;
+use crate::technology::TechnologyType;
+
+// Город с запасом золота и кристаллов на любое обучение
+fn rich_city() -> City {
+    let mut city = city("Новгород", "alice", (0, 0));
+    city.resources
+        .apply(
+            LedgerEntry::new(LedgerReason::Adjustment)
+                .credit(ResourceType::Gold, 1000)
+                .credit(ResourceType::Iron, 100)
+                .credit(ResourceType::Crystal, 100),
+        )
+        .unwrap();
+    city
+}
+
+// Выполняет условие перевода
+fn satisfy(city: &mut City, requirement: &ConversionRequirement) {
+    match requirement {
+        ConversionRequirement::None => {}
+        ConversionRequirement::Building(building_type) => {
+            place(city, building_type.clone(), (1, 0));
+        }
+        ConversionRequirement::Technology(tech_type) => {
+            city.research.completed.insert(tech_type.clone());
+        }
+        ConversionRequirement::Culture(level) => city.stats.culture = *level,
+    }
+}
+
+#[test]
+fn every_rule_is_blocked_until_requirement_is_met() {
+    for rule in ConversionRule::all() {
+        let mut city = rich_city();
+
+        if rule.requirement != ConversionRequirement::None {
+            let gold = city.resources.get(&ResourceType::Gold);
+            assert!(
+                city.train_citizens(rule.from.clone(), rule.to.clone(), 1)
+                    .is_err(),
+                "{:?} -> {:?} без условия",
+                rule.from,
+                rule.to
+            );
+            assert_eq!(city.resources.get(&ResourceType::Gold), gold);
+        }
+
+        satisfy(&mut city, &rule.requirement);
+        let from = city.population.count(&rule.from);
+        let to = city.population.count(&rule.to);
+        city.train_citizens(rule.from.clone(), rule.to.clone(), 1)
+            .unwrap();
+        assert_eq!(city.population.count(&rule.from), from - 1);
+
+        for _ in 0..rule.duration {
+            assert_eq!(city.population.count(&rule.to), to);
+            city.update_mobility();
+        }
+        assert_eq!(
+            city.population.count(&rule.to),
+            to + 1,
+            "{:?} -> {:?}",
+            rule.from,
+            rule.to
+        );
+    }
+}
+
+#[test]
+fn culture_gate_requires_exact_level() {
+    let mut city = rich_city();
+    city.stats.culture = 29;
+    assert!(
+        city.train_citizens(PopulationClass::Merchant, PopulationClass::Noble, 1)
+            .is_err()
+    );
+
+    city.stats.culture = 30;
+    city.train_citizens(PopulationClass::Merchant, PopulationClass::Noble, 1)
+        .unwrap();
+}
+
+#[test]
+fn training_charges_cost_per_citizen() {
+    let mut city = rich_city();
+    place(&mut city, BuildingType::Barracks, (1, 0));
+    let gold = city.resources.get(&ResourceType::Gold);
+    let iron = city.resources.get(&ResourceType::Iron);
+
+    city.train_citizens(PopulationClass::Peasant, PopulationClass::Soldier, 3)
+        .unwrap();
+
+    assert_eq!(city.resources.get(&ResourceType::Gold), gold - 30);
+    assert_eq!(city.resources.get(&ResourceType::Iron), iron - 6);
+    assert_eq!(city.mobility.in_training(), 3);
+}
+
+#[test]
+fn unknown_conversion_is_rejected() {
+    let mut city = rich_city();
+    assert!(
+        city.train_citizens(PopulationClass::Noble, PopulationClass::Peasant, 1)
+            .is_err()
+    );
+    assert!(
+        city.train_citizens(PopulationClass::Peasant, PopulationClass::Soldier, 0)
+            .is_err()
+    );
+}
+
+#[test]
+fn cancelled_training_returns_citizens_without_refund() {
+    let mut city = rich_city();
+    let peasants = city.population.count(&PopulationClass::Peasant);
+    let gold = city.resources.get(&ResourceType::Gold);
+    city.research.completed.insert(TechnologyType::Education);
+
+    let id = city
+        .train_citizens(PopulationClass::Peasant, PopulationClass::Scholar, 2)
+        .unwrap();
+    city.cancel_training(&id).unwrap();
+
+    assert_eq!(city.population.count(&PopulationClass::Peasant), peasants);
+    assert_eq!(city.resources.get(&ResourceType::Gold), gold - 50);
+    assert!(city.mobility.conversions.is_empty());
+}
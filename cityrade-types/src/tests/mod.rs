@@ -1 +1,47 @@
+use crate::building::{Building, BuildingType};
+use crate::city::{City, Terrain};
 
+mod adjacency;
+mod budget;
+mod caravan;
+mod chunk;
+mod construction;
+mod content;
+mod crafting;
+mod demolition;
+mod game;
+mod labor;
+mod maintenance;
+mod market;
+mod mobility;
+mod needs;
+mod power;
+mod production;
+mod research;
+mod resources;
+mod storage;
+mod technology;
+mod world;
+
+// Город на равнине с начальными запасами и населением
+fn city(name: &str, owner: &str, position: (i32, i32)) -> City {
+    City::new(
+        name.to_string(),
+        owner.to_string(),
+        Terrain::Plain,
+        position,
+    )
+}
+
+// Ставит готовое здание в обход очереди строительства
+fn place(city: &mut City, building_type: BuildingType, position: (i32, i32)) -> String {
+    let id = format!("{:?}-{}-{}", building_type, position.0, position.1);
+    let building = Building::new(
+        id.clone(),
+        format!("{:?}", building_type),
+        building_type,
+        position,
+    );
+    city.buildings.insert(id.clone(), building);
+    id
+}
@@ -0,0 +1,91 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::ledger::{LedgerEntry, LedgerReason};
+use crate::needs::{self, HappinessFactor};
+use crate::population::{Population, PopulationClass};
+use crate::resources::{ResourceType, Resources};
+
+// Население из одного класса
+fn only(class: PopulationClass, count: u32) -> Population {
+    let mut population = Population::new();
+    population.classes.clear();
+    population.classes.insert(class, count);
+    population
+}
+
+#[test]
+fn fractional_demand_is_rounded_up() {
+    let population = only(PopulationClass::Noble, 1);
+    let mut resources = Resources::new();
+    resources
+        .apply(LedgerEntry::new(LedgerReason::Adjustment).credit(ResourceType::Crystal, 3))
+        .unwrap();
+    let food = resources.get(&ResourceType::Food);
+
+    let satisfaction = needs::consume(&population, &mut resources);
+
+    assert_eq!(resources.get(&ResourceType::Crystal), 2);
+    assert_eq!(resources.get(&ResourceType::Food), food - 1);
+    assert_eq!(satisfaction[&ResourceType::Crystal], 1.0);
+}
+
+#[test]
+fn whole_demand_is_not_overcharged() {
+    let population = only(PopulationClass::Worker, 10);
+    let mut resources = Resources::new();
+    let energy = resources.get(&ResourceType::Energy);
+
+    needs::consume(&population, &mut resources);
+
+    assert_eq!(resources.get(&ResourceType::Energy), energy - 2);
+}
+
+#[test]
+fn missing_luxury_is_a_shortage() {
+    let population = only(PopulationClass::Noble, 1);
+    let mut resources = Resources::new();
+
+    let satisfaction = needs::consume(&population, &mut resources);
+
+    assert_eq!(satisfaction[&ResourceType::Crystal], 0.0);
+    let factors = needs::needs_factors(&PopulationClass::Noble, 0.5, &satisfaction);
+    assert!(factors.contains(&(HappinessFactor::Shortage(ResourceType::Crystal), -0.15)));
+    assert!(!factors.iter().any(|(f, _)| *f == HappinessFactor::NeedsMet));
+}
+
+#[test]
+fn breakdown_explains_happiness_change() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    place(&mut city, BuildingType::Temple, (1, 0));
+    let previous = city.population.class_happiness(&PopulationClass::Noble);
+
+    city.update_population();
+
+    // У знати нет кристаллов, крестьяне обеспечены едой
+    let noble = city.happiness_breakdown(&PopulationClass::Noble).unwrap();
+    assert!(
+        noble
+            .factors
+            .iter()
+            .any(|(f, _)| *f == HappinessFactor::Shortage(ResourceType::Crystal))
+    );
+    assert!(
+        noble
+            .factors
+            .iter()
+            .any(|(f, _)| *f == HappinessFactor::Temples)
+    );
+    assert_eq!(noble.previous, previous);
+    assert!(
+        (noble.current - city.population.class_happiness(&PopulationClass::Noble)).abs() < 1e-6
+    );
+    assert!(noble.change() * (noble.target() - previous) >= 0.0);
+
+    let peasant = city.happiness_breakdown(&PopulationClass::Peasant).unwrap();
+    assert!(
+        peasant
+            .factors
+            .iter()
+            .any(|(f, _)| *f == HappinessFactor::NeedsMet)
+    );
+}
@@ -0,0 +1,67 @@
+use crate::building::BuildingType;
+use crate::power::distribute;
+use std::collections::HashMap;
+
+fn consumer(id: &str, demand: u32) -> (String, BuildingType, u32) {
+    (id.to_string(), BuildingType::Workshop, demand)
+}
+
+#[test]
+fn demand_within_supply_is_fully_met() {
+    let report = distribute(
+        10,
+        5,
+        vec![consumer("a", 6), consumer("b", 9)],
+        &HashMap::new(),
+    );
+
+    assert!(!report.is_brownout());
+    assert_eq!(report.consumed(), 15);
+    assert_eq!(report.net(), -5);
+}
+
+#[test]
+fn brownout_is_shared_proportionally_within_a_tier() {
+    // 10 выработки и 5 запаса на 30 единиц спроса: каждому по половине
+    let report = distribute(
+        10,
+        5,
+        vec![consumer("a", 10), consumer("b", 20)],
+        &HashMap::new(),
+    );
+
+    assert!(report.is_brownout());
+    assert_eq!(report.ratio("a"), 0.5);
+    assert_eq!(report.ratio("b"), 0.5);
+    assert_eq!(report.consumed(), 15);
+}
+
+#[test]
+fn higher_priority_is_supplied_first() {
+    let mut priorities = HashMap::new();
+    priorities.insert("b".to_string(), 5);
+
+    let report = distribute(
+        16,
+        0,
+        vec![consumer("a", 10), consumer("b", 8), consumer("c", 6)],
+        &priorities,
+    );
+
+    assert_eq!(report.ratio("b"), 1.0);
+    assert_eq!(report.ratio("a"), 0.5);
+    assert_eq!(report.ratio("c"), 0.5);
+    // Здания без потребления работают в полную силу
+    assert_eq!(report.ratio("unknown"), 1.0);
+}
+
+#[test]
+fn huge_reserves_do_not_overflow() {
+    let report = distribute(
+        u32::MAX,
+        u32::MAX,
+        vec![consumer("a", 100)],
+        &HashMap::new(),
+    );
+    assert_eq!(report.ratio("a"), 1.0);
+}
@@ -0,0 +1,310 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::city::{City, DEPOSIT_WORK_RADIUS, PRODUCED_RESOURCES, Terrain};
+use crate::ledger::{LedgerEntry, LedgerReason};
+use crate::population::PopulationClass;
+use crate::resources::ResourceType;
+use crate::world::Deposit;
+
+// Здания города и их позиции
+type Layout = &'static [(BuildingType, (i32, i32))];
+
+// Наборы зданий для эталонных проверок. Здания ставятся с зазором, чтобы
+// соседство не влияло на результат, кроме набора `MILLS`
+const EMPTY: Layout = &[];
+const RAW: Layout = &[
+    (BuildingType::Farm, (0, 0)),
+    (BuildingType::LumberMill, (3, 0)),
+    (BuildingType::Mine, (6, 0)),
+];
+const GOLD: Layout = &[
+    (BuildingType::Market, (0, 0)),
+    (BuildingType::Temple, (3, 0)),
+    (BuildingType::Barracks, (6, 0)),
+];
+const ENERGY: Layout = &[
+    (BuildingType::PowerPlant, (0, 0)),
+    (BuildingType::Workshop, (3, 0)),
+    (BuildingType::CrystalMine, (6, 0)),
+];
+const MILLS: Layout = &[
+    (BuildingType::Farm, (0, 0)),
+    (BuildingType::WaterMill, (1, 0)),
+];
+
+fn city_with(terrain: Terrain, buildings: Layout) -> City {
+    let mut city = city("Тест", "owner", (0, 0));
+    city.terrain = terrain;
+    for (building_type, position) in buildings {
+        place(&mut city, building_type.clone(), *position);
+    }
+    city
+}
+
+// Город с одним зданием в центре
+fn city_with_one(building_type: BuildingType) -> (City, String) {
+    let mut city = city("Тест", "owner", (0, 0));
+    let id = place(&mut city, building_type, (0, 0));
+    (city, id)
+}
+
+// Скорости производства в порядке PRODUCED_RESOURCES
+fn rates(city: &City) -> Vec<i32> {
+    PRODUCED_RESOURCES
+        .iter()
+        .map(|resource| city.resources.get_production_rate(resource))
+        .collect()
+}
+
+// Эталонные скорости производства (Gold, Wood, Stone, Food, Iron, Crystal,
+// Energy) для каждой местности и набора зданий. При осознанном изменении
+// баланса таблицу нужно обновить вместе с контентом
+#[rustfmt::skip]
+const GOLDEN: &[(Terrain, Layout, [i32; 7])] = &[
+    (Terrain::Plain,    EMPTY,  [10,  3,  2, 10, 0, 0,  0]),
+    (Terrain::Plain,    RAW,    [10, 14,  8, 27, 2, 0,  0]),
+    (Terrain::Plain,    GOLD,   [ 6,  3,  2,  4, 0, 0,  0]),
+    (Terrain::Plain,    ENERGY, [23,  3,  2, 10, 0, 1, 14]),
+    (Terrain::Plain,    MILLS,  [10,  9,  2, 39, 0, 0,  0]),
+    (Terrain::Forest,   EMPTY,  [10,  5,  2,  7, 0, 0,  0]),
+    (Terrain::Forest,   RAW,    [10, 20,  8, 18, 2, 0,  0]),
+    (Terrain::Forest,   GOLD,   [ 6,  5,  2,  1, 0, 0,  0]),
+    (Terrain::Forest,   ENERGY, [23,  5,  2,  7, 0, 1, 14]),
+    (Terrain::Forest,   MILLS,  [10, 14,  2, 26, 0, 0,  0]),
+    (Terrain::Mountain, EMPTY,  [10,  3,  3,  5, 0, 0,  0]),
+    (Terrain::Mountain, RAW,    [10, 14, 12, 14, 3, 0,  0]),
+    (Terrain::Mountain, GOLD,   [ 6,  3,  3, -1, 0, 0,  0]),
+    (Terrain::Mountain, ENERGY, [23,  3,  3,  5, 0, 1, 14]),
+    (Terrain::Mountain, MILLS,  [10,  9,  3, 19, 0, 0,  0]),
+    (Terrain::Desert,   EMPTY,  [10,  1,  2,  4, 0, 0,  0]),
+    (Terrain::Desert,   RAW,    [10,  4,  8, 11, 2, 0,  0]),
+    (Terrain::Desert,   GOLD,   [ 6,  1,  2, -2, 0, 0,  0]),
+    (Terrain::Desert,   ENERGY, [23,  1,  2,  4, 0, 1, 14]),
+    (Terrain::Desert,   MILLS,  [10,  3,  2, 16, 0, 0,  0]),
+    (Terrain::Swamp,    EMPTY,  [10,  3,  2,  6, 0, 0,  0]),
+    (Terrain::Swamp,    RAW,    [10, 15,  8, 16, 2, 0,  0]),
+    (Terrain::Swamp,    GOLD,   [ 6,  3,  2,  0, 0, 0,  0]),
+    (Terrain::Swamp,    ENERGY, [23,  3,  2,  6, 0, 1, 14]),
+    (Terrain::Swamp,    MILLS,  [10, 10,  2, 23, 0, 0,  0]),
+    (Terrain::Water,    EMPTY,  [11,  3,  2, 11, 0, 0,  0]),
+    (Terrain::Water,    RAW,    [11, 14,  8, 29, 2, 0,  0]),
+    (Terrain::Water,    GOLD,   [ 8,  3,  2,  5, 0, 0,  0]),
+    (Terrain::Water,    ENERGY, [25,  3,  2, 11, 0, 1, 14]),
+    (Terrain::Water,    MILLS,  [11,  9,  2, 42, 0, 0,  0]),
+    (Terrain::Snow,     EMPTY,  [10,  3,  2,  3, 0, 0,  0]),
+    (Terrain::Snow,     RAW,    [10, 14,  8,  9, 2, 0,  0]),
+    (Terrain::Snow,     GOLD,   [ 6,  3,  2, -3, 0, 0,  0]),
+    (Terrain::Snow,     ENERGY, [23,  3,  2,  3, 0, 1,  8]),
+    (Terrain::Snow,     MILLS,  [10,  9,  2, 13, 0, 0,  0]),
+];
+
+#[test]
+fn golden_rates_per_terrain_and_buildings() {
+    for (terrain, buildings, expected) in GOLDEN {
+        let mut city = city_with(terrain.clone(), buildings);
+        city.update_resource_production();
+        assert_eq!(
+            rates(&city),
+            expected.to_vec(),
+            "{:?} с зданиями {:?}",
+            terrain,
+            buildings
+        );
+    }
+}
+
+#[test]
+fn golden_covers_every_terrain() {
+    for terrain in Terrain::all() {
+        assert!(
+            GOLDEN.iter().any(|(t, _, _)| *t == terrain),
+            "{:?}",
+            terrain
+        );
+    }
+}
+
+#[test]
+fn breakdown_sums_to_rate() {
+    for (terrain, buildings, _) in GOLDEN {
+        let mut city = city_with(terrain.clone(), buildings);
+        city.update_resource_production();
+        for resource in PRODUCED_RESOURCES {
+            assert_eq!(
+                city.production.total(&resource),
+                city.resources.get_production_rate(&resource),
+                "{:?} {:?}",
+                terrain,
+                resource
+            );
+        }
+    }
+}
+
+#[test]
+fn breakdown_matches_applied_income() {
+    for (terrain, buildings, _) in GOLDEN {
+        let mut city = city_with(terrain.clone(), buildings);
+        city.update_resource_production();
+        let before: Vec<i64> = PRODUCED_RESOURCES
+            .iter()
+            .map(|resource| city.resources.get(resource) as i64)
+            .collect();
+
+        city.resources.update_production();
+
+        let applied = city.resources.ledger().recent(1).next().unwrap().net();
+        for (resource, before) in PRODUCED_RESOURCES.iter().zip(before) {
+            let total = city.production.total(resource) as i64;
+            assert_eq!(
+                city.resources.get(resource) as i64 - before,
+                total,
+                "{:?} {:?}",
+                terrain,
+                resource
+            );
+            assert_eq!(*applied.get(resource).unwrap_or(&0), total);
+        }
+        assert_eq!(
+            city.budget.balance(),
+            city.production.total(&ResourceType::Gold),
+            "{:?}",
+            terrain
+        );
+    }
+}
+
+#[test]
+fn rates_do_not_accumulate_between_ticks() {
+    let mut city = city_with(Terrain::Plain, RAW);
+    city.update_resource_production();
+    let first = rates(&city);
+    city.update_resource_production();
+    assert_eq!(rates(&city), first);
+}
+
+#[test]
+fn population_is_not_produced() {
+    let (mut city, _) = city_with_one(BuildingType::Residential);
+    assert_eq!(
+        city.resources
+            .get_production_rate(&ResourceType::Population),
+        0
+    );
+    city.update_resource_production();
+    assert_eq!(
+        city.resources
+            .get_production_rate(&ResourceType::Population),
+        0
+    );
+}
+
+#[test]
+fn deposit_boosts_matching_building_until_depleted() {
+    let (mut city, _) = city_with_one(BuildingType::Mine);
+    city.update_resource_production();
+    let without = city.resources.get_production_rate(&ResourceType::Iron);
+
+    city.deposits
+        .push(Deposit::new((0, 0), ResourceType::Iron, 1, 0));
+    city.update_resource_production();
+    let with = city.resources.get_production_rate(&ResourceType::Iron);
+    assert!(with > without, "{} > {}", with, without);
+    assert!(city.deposits[0].is_depleted());
+
+    city.update_resource_production();
+    assert_eq!(
+        city.resources.get_production_rate(&ResourceType::Iron),
+        without
+    );
+}
+
+#[test]
+fn deposit_far_from_building_gives_no_bonus() {
+    let (mut city, _) = city_with_one(BuildingType::Mine);
+    city.update_resource_production();
+    let without = city.resources.get_production_rate(&ResourceType::Iron);
+
+    // Месторождение принадлежит городу, но шахта стоит далеко от него
+    city.deposits.push(Deposit::new(
+        (DEPOSIT_WORK_RADIUS + 1, 0),
+        ResourceType::Iron,
+        100,
+        0,
+    ));
+    city.update_resource_production();
+
+    assert_eq!(
+        city.resources.get_production_rate(&ResourceType::Iron),
+        without
+    );
+    assert_eq!(city.deposits[0].amount, 100);
+}
+
+#[test]
+fn nearest_deposit_is_drawn_first() {
+    let (mut city, _) = city_with_one(BuildingType::Mine);
+    city.deposits
+        .push(Deposit::new((1, 1), ResourceType::Iron, 100, 0));
+    city.deposits
+        .push(Deposit::new((0, 0), ResourceType::Iron, 100, 0));
+
+    city.update_resource_production();
+
+    assert_eq!(city.deposits[0].amount, 100);
+    assert!(city.deposits[1].amount < 100);
+}
+
+#[test]
+fn idle_building_does_not_deplete_deposit() {
+    let (mut city, _) = city_with_one(BuildingType::Mine);
+    city.population.classes.insert(PopulationClass::Worker, 0);
+    city.deposits
+        .push(Deposit::new((0, 0), ResourceType::Iron, 100, 0));
+
+    city.update_resource_production();
+
+    assert_eq!(city.deposits[0].amount, 100);
+}
+
+#[test]
+fn unpowered_building_does_not_deplete_deposit() {
+    let (mut city, _) = city_with_one(BuildingType::CrystalMine);
+    city.deposits
+        .push(Deposit::new((0, 0), ResourceType::Crystal, 100, 0));
+    city.update_resource_production();
+    assert!(city.deposits[0].amount < 100);
+
+    // Без выработки и запаса энергии шахта стоит
+    let (mut city, id) = city_with_one(BuildingType::CrystalMine);
+    let energy = city.resources.get(&ResourceType::Energy);
+    city.resources
+        .apply(LedgerEntry::new(LedgerReason::Adjustment).debit(ResourceType::Energy, energy))
+        .unwrap();
+    city.deposits
+        .push(Deposit::new((0, 0), ResourceType::Crystal, 100, 0));
+    city.update_resource_production();
+
+    assert_eq!(city.power.ratio(&id), 0.0);
+    assert_eq!(city.deposits[0].amount, 100);
+    assert_eq!(
+        city.resources.get_production_rate(&ResourceType::Crystal),
+        0
+    );
+}
+
+#[test]
+fn deposits_are_matched_in_world_coordinates() {
+    let mut city = city("Тест", "owner", (40, -20));
+    place(&mut city, BuildingType::Mine, (1, 0));
+    // Второе месторождение лежит у клетки (1, 0) мира, а не у шахты
+    city.deposits
+        .push(Deposit::new((41, -20), ResourceType::Iron, 100, 0));
+    city.deposits
+        .push(Deposit::new((1, 0), ResourceType::Iron, 100, 0));
+
+    city.update_resource_production();
+
+    assert!(city.deposits[0].amount < 100);
+    assert_eq!(city.deposits[1].amount, 100);
+}
@@ -0,0 +1,87 @@
+use super::city;
+use crate::building::BuildingType;
+use crate::game::{Command, Game, GameEvent};
+use crate::technology::{ResearchEvent, ResearchStatus, TechnologyType};
+
+#[test]
+fn prerequisites_must_be_queued_first() {
+    let mut city = city("Новгород", "alice", (0, 0));
+
+    assert!(city.start_research(TechnologyType::Trade).is_err());
+    city.start_research(TechnologyType::Agriculture).unwrap();
+    city.start_research(TechnologyType::Trade).unwrap();
+    assert!(city.start_research(TechnologyType::Agriculture).is_err());
+    assert_eq!(
+        city.research.queue,
+        [TechnologyType::Agriculture, TechnologyType::Trade]
+    );
+}
+
+#[test]
+fn city_update_completes_queued_research() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    city.start_research(TechnologyType::Agriculture).unwrap();
+
+    let mut events = Vec::new();
+    for _ in 0..100 {
+        events.extend(city.update());
+        if !events.is_empty() {
+            break;
+        }
+    }
+
+    assert_eq!(
+        events,
+        [ResearchEvent::Completed(TechnologyType::Agriculture)]
+    );
+    assert!(city.is_researched(&TechnologyType::Agriculture));
+    assert!(city.research.queue.is_empty());
+}
+
+#[test]
+fn research_unlocks_buildings() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    assert!(
+        city.add_building(BuildingType::Farm, "Ферма".to_string(), (1, 1))
+            .is_err()
+    );
+
+    city.research.completed.insert(TechnologyType::Agriculture);
+    city.add_building(BuildingType::Farm, "Ферма".to_string(), (1, 1))
+        .unwrap();
+}
+
+#[test]
+fn game_queues_research_for_the_owner_and_reports_completion() {
+    let mut game = Game::new(1);
+    game.add_city(city("Новгород", "alice", (0, 0))).unwrap();
+
+    let command = Command::StartResearch {
+        city: "Новгород".to_string(),
+        technology: TechnologyType::Agriculture,
+    };
+    assert!(game.execute("bob", command.clone()).is_err());
+    game.execute("alice", command).unwrap();
+    assert_eq!(
+        game.cities["Новгород"].research.status(
+            &crate::content::current().technologies,
+            &TechnologyType::Agriculture
+        ),
+        ResearchStatus::InProgress
+    );
+
+    let mut events = Vec::new();
+    for _ in 0..100 {
+        events.extend(game.tick());
+        if !events.is_empty() {
+            break;
+        }
+    }
+    assert_eq!(
+        events,
+        [GameEvent::Research {
+            city: "Новгород".to_string(),
+            event: ResearchEvent::Completed(TechnologyType::Agriculture),
+        }]
+    );
+}
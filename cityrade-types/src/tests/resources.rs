@@ -0,0 +1,89 @@
+use super::city;
+use crate::ledger::{LedgerEntry, LedgerReason};
+use crate::resources::{ResourceType, Resources};
+
+// Все запасы в постоянном порядке
+fn balances(resources: &Resources) -> Vec<(ResourceType, u32)> {
+    let mut balances = resources.get_all_resources();
+    balances.sort_by_key(|(resource, _)| format!("{:?}", resource));
+    balances
+}
+
+#[test]
+fn failed_transaction_leaves_balances_unchanged() {
+    let mut resources = Resources::new();
+    let before = balances(&resources);
+    let entries = resources.ledger().entries.len();
+
+    // Дерева хватает, железа нет: не списывается ничего
+    let result = resources.apply(
+        LedgerEntry::new(LedgerReason::Construction)
+            .debit(ResourceType::Wood, 10)
+            .debit(ResourceType::Iron, 5)
+            .credit(ResourceType::Gold, 7),
+    );
+
+    assert!(result.unwrap_err().contains("Железо"));
+    assert_eq!(balances(&resources), before);
+    assert_eq!(resources.ledger().entries.len(), entries);
+    assert!(!resources.pay(LedgerReason::Repair, &[(ResourceType::Iron, 1)]));
+}
+
+#[test]
+fn partial_transaction_debits_what_is_available() {
+    let mut resources = Resources::new();
+    let wood = resources.get(&ResourceType::Wood);
+
+    let applied = resources.apply_partial(
+        LedgerEntry::new(LedgerReason::Consumption)
+            .debit(ResourceType::Wood, 10)
+            .debit(ResourceType::Iron, 5),
+    );
+
+    assert_eq!(applied.deltas, [(ResourceType::Wood, -10)]);
+    assert_eq!(resources.get(&ResourceType::Wood), wood - 10);
+    assert_eq!(resources.get(&ResourceType::Iron), 0);
+}
+
+#[test]
+fn every_change_is_logged_with_its_reason() {
+    let mut resources = Resources::new();
+
+    resources
+        .apply(LedgerEntry::new(LedgerReason::Trade).credit(ResourceType::Iron, 4))
+        .unwrap();
+    assert!(resources.pay(LedgerReason::Construction, &[(ResourceType::Stone, 5)]));
+    resources.set_production_rate(ResourceType::Food, -3);
+    resources.update_production();
+
+    let recent: Vec<_> = resources
+        .ledger()
+        .recent(3)
+        .map(|entry| (entry.reason.clone(), entry.deltas.clone()))
+        .collect();
+    assert_eq!(
+        recent,
+        [
+            (LedgerReason::Production, vec![(ResourceType::Food, -3)]),
+            (LedgerReason::Construction, vec![(ResourceType::Stone, -5)]),
+            (LedgerReason::Trade, vec![(ResourceType::Iron, 4)]),
+        ]
+    );
+}
+
+#[test]
+fn population_resource_follows_citizens_through_ledger() {
+    let mut city = city("Новгород", "alice", (0, 0));
+
+    city.update();
+
+    let citizens = city.population.total() + city.mobility.in_training();
+    assert_eq!(city.resources.get(&ResourceType::Population), citizens);
+    assert!(city.resources.ledger().recent(usize::MAX).any(|entry| {
+        entry.reason == LedgerReason::Adjustment
+            && entry
+                .deltas
+                .iter()
+                .any(|(resource, _)| *resource == ResourceType::Population)
+    }));
+}
@@ -0,0 +1,72 @@
+use super::{city, place};
+use crate::building::BuildingType;
+use crate::ledger::{LedgerEntry, LedgerReason};
+use crate::resources::ResourceType;
+use crate::storage::{self, FOOD_SPOILAGE_RATE, GRANARY_SPOILAGE_FACTOR};
+
+#[test]
+fn storage_buildings_add_capacity() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let warehouse = place(&mut city, BuildingType::Warehouse, (1, 1));
+    city.buildings.get_mut(&warehouse).unwrap().level = 2;
+
+    let capacities = storage::capacities(city.buildings.values());
+
+    assert_eq!(capacities[&ResourceType::Wood], 500 + 600);
+    assert_eq!(capacities[&ResourceType::Food], 400);
+    assert!(!capacities.contains_key(&ResourceType::Gold));
+}
+
+#[test]
+fn production_is_clamped_at_capacity() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    city.update_storage();
+    let wood = city.resources.get(&ResourceType::Wood);
+
+    let applied = city
+        .resources
+        .apply(LedgerEntry::new(LedgerReason::Production).credit(ResourceType::Wood, 1000))
+        .unwrap();
+
+    assert_eq!(city.resources.get(&ResourceType::Wood), 500);
+    assert_eq!(applied.deltas, [(ResourceType::Wood, (500 - wood) as i64)]);
+
+    city.update_storage();
+    assert_eq!(
+        city.storage.overflow[&ResourceType::Wood],
+        1000 - (500 - wood)
+    );
+
+    // Потери учитываются только один раз
+    city.update_storage();
+    assert!(city.storage.overflow.is_empty());
+}
+
+#[test]
+fn food_spoils_every_tick() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    let food = city.resources.get(&ResourceType::Food);
+    let rotten = (food as f32 * FOOD_SPOILAGE_RATE) as u32;
+
+    city.update_storage();
+
+    assert_eq!(city.resources.get(&ResourceType::Food), food - rotten);
+    assert_eq!(city.storage.spoiled[&ResourceType::Food], rotten);
+    assert_eq!(
+        city.resources.ledger().recent(1).next().unwrap().reason,
+        LedgerReason::Spoilage
+    );
+}
+
+#[test]
+fn granary_slows_spoilage() {
+    let mut city = city("Новгород", "alice", (0, 0));
+    place(&mut city, BuildingType::Granary, (1, 1));
+    let food = city.resources.get(&ResourceType::Food);
+    let rotten = (food as f32 * FOOD_SPOILAGE_RATE * GRANARY_SPOILAGE_FACTOR) as u32;
+
+    city.update_storage();
+
+    assert_eq!(city.storage.capacity[&ResourceType::Food], 800);
+    assert_eq!(city.resources.get(&ResourceType::Food), food - rotten);
+}
@@ -0,0 +1,139 @@
+use crate::building::BuildingType;
+use crate::resources::ResourceType;
+use crate::technology::{TechEffect, Technology, TechnologyTree, TechnologyType};
+use std::collections::HashSet;
+
+fn tech(tech_type: TechnologyType, effects: Vec<TechEffect>) -> Technology {
+    Technology {
+        name: format!("{:?}", tech_type),
+        tech_type,
+        description: String::new(),
+        cost: 10,
+        prerequisites: Vec::new(),
+        unlock_effects: effects,
+    }
+}
+
+// Технология без эффектов с заданными предпосылками
+fn requires(tech_type: TechnologyType, prerequisites: Vec<TechnologyType>) -> Technology {
+    Technology {
+        prerequisites,
+        ..tech(tech_type, Vec::new())
+    }
+}
+
+fn multiplier(multiplier: f32) -> TechEffect {
+    TechEffect::ProductionMultiplier {
+        resource: ResourceType::Food,
+        multiplier,
+    }
+}
+
+#[test]
+fn effects_follow_declaration_order() {
+    let tree = TechnologyTree::from_technologies(vec![
+        tech(TechnologyType::Culture, vec![multiplier(1.3)]),
+        tech(TechnologyType::Agriculture, vec![multiplier(1.1)]),
+        tech(TechnologyType::Trade, vec![multiplier(1.2)]),
+    ]);
+    let completed: HashSet<TechnologyType> = [
+        TechnologyType::Trade,
+        TechnologyType::Culture,
+        TechnologyType::Agriculture,
+    ]
+    .into_iter()
+    .collect();
+
+    let effects: Vec<&TechEffect> = tree.effects_of(&completed).collect();
+    assert_eq!(
+        effects,
+        [&multiplier(1.1), &multiplier(1.2), &multiplier(1.3)]
+    );
+}
+
+#[test]
+fn first_declared_technology_unlocks_shared_building() {
+    // Каждое дерево получает свое зерно хеша, поэтому проверяем несколько
+    for _ in 0..10 {
+        let unlock = || vec![TechEffect::UnlockBuilding(BuildingType::Market)];
+        let tree = TechnologyTree::from_technologies(vec![
+            tech(TechnologyType::Banking, unlock()),
+            tech(TechnologyType::Trade, unlock()),
+        ]);
+
+        let required = tree.required_technology(&BuildingType::Market).unwrap();
+        assert_eq!(required.tech_type, TechnologyType::Trade);
+    }
+}
+
+#[test]
+fn base_and_linear_trees_are_valid() {
+    crate::content::GameContent::base()
+        .technologies
+        .validate()
+        .unwrap();
+
+    let tree = TechnologyTree::from_technologies(vec![
+        requires(TechnologyType::Agriculture, vec![]),
+        requires(TechnologyType::Trade, vec![TechnologyType::Agriculture]),
+        requires(
+            TechnologyType::Banking,
+            vec![TechnologyType::Trade, TechnologyType::Agriculture],
+        ),
+    ]);
+    assert!(tree.validate().is_ok());
+}
+
+#[test]
+fn missing_prerequisite_is_rejected() {
+    let tree = TechnologyTree::from_technologies(vec![requires(
+        TechnologyType::Trade,
+        vec![TechnologyType::Agriculture],
+    )]);
+    let error = tree.validate().unwrap_err();
+    assert!(error.contains("Agriculture"), "{}", error);
+}
+
+#[test]
+fn technology_under_foreign_key_is_rejected() {
+    let tree: TechnologyTree = ron::from_str(
+        r#"(technologies: {
+            Trade: (
+                tech_type: Banking,
+                name: "Банковское дело",
+                description: "",
+                cost: 10,
+                prerequisites: [],
+                unlock_effects: [],
+            ),
+        })"#,
+    )
+    .unwrap();
+    let error = tree.validate().unwrap_err();
+    assert!(error.contains("Banking"), "{}", error);
+}
+
+#[test]
+fn two_node_cycle_is_rejected() {
+    let tree = TechnologyTree::from_technologies(vec![
+        requires(TechnologyType::Trade, vec![TechnologyType::Banking]),
+        requires(TechnologyType::Banking, vec![TechnologyType::Trade]),
+    ]);
+    let error = tree.validate().unwrap_err();
+    assert!(error.contains("Циклическая"), "{}", error);
+}
+
+#[test]
+fn three_node_cycle_is_rejected() {
+    let tree = TechnologyTree::from_technologies(vec![
+        requires(TechnologyType::Agriculture, vec![]),
+        requires(
+            TechnologyType::Trade,
+            vec![TechnologyType::Agriculture, TechnologyType::Culture],
+        ),
+        requires(TechnologyType::Banking, vec![TechnologyType::Trade]),
+        requires(TechnologyType::Culture, vec![TechnologyType::Banking]),
+    ]);
+    let error = tree.validate().unwrap_err();
+    assert!(error.contains("Циклическая"), "{}", error);
+}
@@ -0,0 +1,169 @@
+use crate::resources::ResourceType;
+use crate::world::{Deposit, TerrainTile, WorldGenerator, WorldMap};
+
+fn tiles(world: &WorldMap) -> Vec<TerrainTile> {
+    let mut tiles = Vec::new();
+    for x in 0..world.get_width() as i32 {
+        for y in 0..world.get_height() as i32 {
+            tiles.push(
+                world
+                    .get_tile(x, y)
+                    .cloned()
+                    .unwrap_or(TerrainTile::Unknown),
+            );
+        }
+    }
+    tiles
+}
+
+#[test]
+fn generation_is_deterministic_from_seed() {
+    let first = WorldGenerator::new(Some(42)).generate(64, 64);
+    let second = WorldGenerator::new(Some(42)).generate(64, 64);
+    let other = WorldGenerator::new(Some(43)).generate(64, 64);
+
+    assert_eq!(tiles(&first), tiles(&second));
+    assert_ne!(tiles(&first), tiles(&other));
+}
+
+#[test]
+fn generation_produces_every_biome() {
+    let world = WorldGenerator::new(Some(42)).generate(160, 160);
+    let tiles = tiles(&world);
+
+    for biome in [
+        TerrainTile::Land,
+        TerrainTile::Water,
+        TerrainTile::Mountain,
+        TerrainTile::Forest,
+        TerrainTile::Desert,
+        TerrainTile::Swamp,
+        TerrainTile::Snow,
+        TerrainTile::River,
+        TerrainTile::Coast,
+    ] {
+        assert!(tiles.contains(&biome), "{:?}", biome);
+    }
+}
+
+#[test]
+fn deposits_match_their_biome() {
+    let world = WorldGenerator::new(Some(42)).generate(160, 160);
+    let mut found = 0;
+
+    for x in 0..world.get_width() as i32 {
+        for y in 0..world.get_height() as i32 {
+            let Some(deposit) = world.get_deposit(x, y) else {
+                continue;
+            };
+            found += 1;
+            let tile = world.get_tile(x, y).unwrap();
+            let expected = match deposit.resource {
+                ResourceType::Iron => vec![TerrainTile::Mountain],
+                ResourceType::Crystal => vec![TerrainTile::Mountain, TerrainTile::Snow],
+                ResourceType::Stone => vec![TerrainTile::Mountain, TerrainTile::Desert],
+                ResourceType::Wood => vec![TerrainTile::Forest, TerrainTile::Swamp],
+                _ => Vec::new(),
+            };
+            assert!(
+                expected.contains(tile),
+                "{:?} на {:?}",
+                deposit.resource,
+                tile
+            );
+            assert_eq!(
+                deposit.is_renewable(),
+                deposit.resource == ResourceType::Wood
+            );
+        }
+    }
+
+    assert!(found > 0);
+}
+
+#[test]
+fn claimed_deposits_leave_the_map() {
+    let mut world = WorldGenerator::new(Some(42)).generate(8, 8);
+    world.add_deposit(Deposit::new((2, 2), ResourceType::Iron, 100, 0));
+
+    let claimed = world.claim_deposits((0, 0), 3);
+
+    assert!(claimed.iter().any(|deposit| deposit.position == (2, 2)));
+    assert!(world.get_deposit(2, 2).is_none());
+}
+
+#[test]
+fn chunks_do_not_depend_on_generation_order() {
+    let generator = WorldGenerator::new(Some(42));
+    let mut forward = WorldMap::unbounded();
+    let mut backward = WorldMap::unbounded();
+
+    generator.explore(&mut forward, (-40, -40), 0);
+    generator.explore(&mut forward, (40, 40), 0);
+    generator.explore(&mut backward, (40, 40), 0);
+    generator.explore(&mut backward, (-40, -40), 0);
+
+    for (x, y) in [(-40, -40), (-33, -60), (40, 40), (63, 33)] {
+        assert_eq!(forward.get_tile(x, y), backward.get_tile(x, y));
+    }
+    assert_eq!(
+        forward.deposits_near((0, 0), 64),
+        backward.deposits_near((0, 0), 64)
+    );
+}
+
+#[test]
+fn explored_chunks_have_no_seams() {
+    let generator = WorldGenerator::new(Some(42));
+    let mut world = WorldMap::unbounded();
+
+    assert_eq!(generator.explore(&mut world, (0, 0), 80), 36);
+    assert_eq!(generator.explore(&mut world, (0, 0), 80), 0);
+
+    // Побережья и реки подходят к воде и через границы чанков
+    let water = |x: i32, y: i32| world.get_tile(x, y) == Some(&TerrainTile::Water);
+    let mut rivers = 0;
+    for x in -80..80 {
+        for y in -80..80 {
+            let tile = world.get_tile(x, y).unwrap();
+            let near_water = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                .into_iter()
+                .any(|(nx, ny)| water(nx, ny));
+            match tile {
+                TerrainTile::Coast => assert!(near_water, "({}, {})", x, y),
+                TerrainTile::Land
+                | TerrainTile::Forest
+                | TerrainTile::Desert
+                | TerrainTile::Swamp => assert!(!near_water, "({}, {})", x, y),
+                TerrainTile::River => rivers += 1,
+                _ => {}
+            }
+        }
+    }
+    assert!(rivers > 0);
+}
+
+#[test]
+fn unexplored_chunks_are_unknown() {
+    let generator = WorldGenerator::new(Some(42));
+    let mut world = WorldMap::unbounded();
+    generator.explore(&mut world, (1000, -1000), 0);
+
+    assert_eq!(world.get_tile(0, 0), Some(&TerrainTile::Unknown));
+    assert_ne!(world.get_tile(1000, -1000), Some(&TerrainTile::Unknown));
+    assert_eq!(world.loaded_chunks(), 1);
+}
+
+#[test]
+fn expanding_matches_generating_at_full_size() {
+    let generator = WorldGenerator::new(Some(42));
+    let mut world = generator.generate(40, 50);
+    generator.expand(&mut world, 100, 70);
+    let full = generator.generate(100, 70);
+
+    assert_eq!(tiles(&world), tiles(&full));
+    assert_eq!(
+        world.deposits_near((50, 35), 50),
+        full.deposits_near((50, 35), 50)
+    );
+}
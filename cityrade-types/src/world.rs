@@ -1,92 +1,450 @@
+use crate::chunk::{self, CHUNK_SIZE, Chunk};
+use crate::city::Terrain;
 use crate::resources::ResourceType;
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
 use rand::{Rng, SeedableRng, rngs::StdRng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+// Размер типичной детали рельефа в клетках
+const TERRAIN_SCALE: f64 = 24.0;
+// Октавы шума: чем больше, тем изрезаннее рельеф
+const TERRAIN_OCTAVES: usize = 4;
+// Высоты от 0.0 до 1.0, разделяющие биомы
+const SEA_LEVEL: f64 = 0.38;
+const MOUNTAIN_LEVEL: f64 = 0.68;
+const SNOW_LEVEL: f64 = 0.8;
+// Влажность от 0.0 до 1.0, разделяющая биомы
+const DESERT_MOISTURE: f64 = 0.32;
+const FOREST_MOISTURE: f64 = 0.56;
+const SWAMP_MOISTURE: f64 = 0.68;
+// Болота бывают только в низинах
+const SWAMP_MAX_ELEVATION: f64 = 0.48;
+// Попыток начать реку в каждом чанке; река начинается только в горах
+const RIVER_ATTEMPTS_PER_CHUNK: u32 = 2;
+// Самая длинная река в чанках. Истоки дальше этого не влияют на чанк
+const RIVER_REACH_CHUNKS: i32 = 2;
+const MAX_RIVER_LENGTH: i32 = RIVER_REACH_CHUNKS * CHUNK_SIZE;
+// Вероятность месторождения на подходящей клетке
+const DEPOSIT_CHANCE: f64 = 0.04;
+// Соль для независимых случайных потоков одного чанка
+const RIVER_SALT: u64 = 1;
+const DEPOSIT_SALT: u64 = 2;
+
+// Готовый чанк и месторождения на нем
+pub struct GeneratedChunk {
+    pub position: (i32, i32),
+    pub chunk: Chunk,
+    pub deposits: Vec<Deposit>,
+}
+
+// Генератор мира. Любой чанк целиком определяется зерном и своими
+// координатами, поэтому мир можно достраивать в любом порядке без швов
 pub struct WorldGenerator {
     seed: u64,
-    rng: StdRng,
+    elevation: Fbm<Perlin>,
+    moisture: Fbm<Perlin>,
 }
 
 impl WorldGenerator {
     pub fn new(seed: Option<u64>) -> Self {
         let seed = seed.unwrap_or_else(|| rand::random());
-        let rng = StdRng::seed_from_u64(seed);
 
-        WorldGenerator { seed, rng }
+        WorldGenerator {
+            seed,
+            elevation: noise_layer(seed),
+            moisture: noise_layer(seed.wrapping_add(1)),
+        }
     }
 
-    pub fn generate(&mut self, width: u64, height: u64) -> WorldMap {
-        let mut world = WorldMap::new(width, height);
-
-        // Генерация водоемов
-        self.generate_water_bodies(&mut world);
-
-        // Генерация ресурсов
-        self.generate_resources(&mut world);
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
 
+    // Карта полностью определяется зерном: одинаковое зерно и размер дают
+    // одинаковый мир
+    pub fn generate(&self, width: u64, height: u64) -> WorldMap {
+        let mut world = WorldMap::new(0, 0);
+        self.expand(&mut world, width, height);
         world
     }
 
-    fn generate_water_bodies(&mut self, world: &mut WorldMap) {
-        let water_bodies_count = (world.get_width() * world.get_height()) as f64 * 0.05;
+    // Расширяет ограниченную карту, генерируя только новую область
+    pub fn expand(&self, world: &mut WorldMap, width: u64, height: u64) {
+        let (old_width, old_height) = (world.get_width() as i32, world.get_height() as i32);
+        let (width, height) = (width.max(world.get_width()), height.max(world.get_height()));
+        world.resize(width, height);
+        let is_new = |x: i32, y: i32| {
+            world_contains(width, height, x, y) && (x >= old_width || y >= old_height)
+        };
 
-        for _ in 0..water_bodies_count as u64 {
-            let x = self.rng.random_range(0..world.get_width()) as i32;
-            let y = self.rng.random_range(0..world.get_height()) as i32;
+        let last = chunk::chunk_position(width as i32 - 1, height as i32 - 1);
+        for chunk_x in 0..=last.0 {
+            for chunk_y in 0..=last.1 {
+                let (x0, y0) = chunk_origin((chunk_x, chunk_y));
+                if x0 + CHUNK_SIZE <= old_width && y0 + CHUNK_SIZE <= old_height {
+                    continue;
+                }
 
-            // Создаем водоем разного размера
-            let size = self.rng.random_range(3..10);
-            self.create_water_body(world, x, y, size);
+                let generated = self.generate_chunk((chunk_x, chunk_y));
+                for x in x0..x0 + CHUNK_SIZE {
+                    for y in y0..y0 + CHUNK_SIZE {
+                        if is_new(x, y) {
+                            world.set_tile(x, y, generated.chunk.get(x, y).clone());
+                        }
+                    }
+                }
+                for deposit in generated.deposits {
+                    if is_new(deposit.position.0, deposit.position.1) {
+                        world.add_deposit(deposit);
+                    }
+                }
+            }
         }
     }
 
-    fn create_water_body(&mut self, world: &mut WorldMap, x: i32, y: i32, size: u32) {
-        for dx in -(size as i32)..=(size as i32) {
-            for dy in -(size as i32)..=(size as i32) {
-                let distance = ((dx * dx + dy * dy) as f64).sqrt();
-                if distance <= size as f64 {
-                    let noise = self.rng.random_range(-1.5..1.5);
-                    if distance + noise <= size as f64 {
-                        let nx = x + dx;
-                        let ny = y + dy;
+    // Генерирует еще не созданные чанки вокруг позиции, например вокруг
+    // отряда разведчиков на безграничной карте. Возвращает число новых чанков
+    pub fn explore(&self, world: &mut WorldMap, position: (i32, i32), radius: i32) -> usize {
+        let from = chunk::chunk_position(position.0 - radius, position.1 - radius);
+        let to = chunk::chunk_position(position.0 + radius, position.1 + radius);
+        let mut generated = 0;
+
+        for chunk_x in from.0..=to.0 {
+            for chunk_y in from.1..=to.1 {
+                if world.is_chunk_loaded((chunk_x, chunk_y)) {
+                    continue;
+                }
+                world.insert_chunk(self.generate_chunk((chunk_x, chunk_y)));
+                generated += 1;
+            }
+        }
 
-                        if nx >= 0
-                            && nx < world.get_width() as i32
-                            && ny >= 0
-                            && ny < world.get_height() as i32
-                        {
-                            world.set_tile(nx, ny, TerrainTile::Water);
+        generated
+    }
+
+    // Генерирует чанк по зерну и координатам. Биомы и побережья считаются по
+    // глобальному шуму, а реки прослеживаются от истоков во всех чанках, до
+    // которых может дотянуться река, поэтому соседние чанки сходятся без швов
+    pub fn generate_chunk(&self, position: (i32, i32)) -> GeneratedChunk {
+        let (x0, y0) = chunk_origin(position);
+        let mut chunk = Chunk::filled(&TerrainTile::Unknown);
+
+        // Биомы из слоев высоты и влажности, побережья вокруг водоемов
+        for x in x0..x0 + CHUNK_SIZE {
+            for y in y0..y0 + CHUNK_SIZE {
+                chunk.set(x, y, &self.tile_at(x, y));
+            }
+        }
+
+        // Реки стекают с гор в водоемы
+        let reach = RIVER_REACH_CHUNKS;
+        for source_x in position.0 - reach..=position.0 + reach {
+            for source_y in position.1 - reach..=position.1 + reach {
+                for source in self.river_sources((source_x, source_y)) {
+                    for (x, y) in self.trace_river(source) {
+                        if chunk::chunk_position(x, y) == position && !chunk.get(x, y).is_water() {
+                            chunk.set(x, y, &TerrainTile::River);
                         }
                     }
                 }
             }
         }
+
+        // Месторождения ресурсов
+        let deposits = self.generate_deposits(position, &chunk);
+
+        GeneratedChunk {
+            position,
+            chunk,
+            deposits,
+        }
     }
 
-    fn generate_resources(&mut self, world: &mut WorldMap) {
-        // Генерация областей с ресурсами (можно привязать к структуре из resources.rs)
-        let resource_spots = (world.get_width() * world.get_height()) as f64 * 0.03;
+    fn elevation_at(&self, x: i32, y: i32) -> f64 {
+        sample(&self.elevation, x, y)
+    }
+
+    fn biome_at(&self, x: i32, y: i32) -> TerrainTile {
+        biome(self.elevation_at(x, y), sample(&self.moisture, x, y))
+    }
+
+    // Клетка до прокладки рек: биом или побережье рядом с водой
+    fn tile_at(&self, x: i32, y: i32) -> TerrainTile {
+        let tile = self.biome_at(x, y);
+        if matches!(
+            tile,
+            TerrainTile::Water | TerrainTile::Mountain | TerrainTile::Snow
+        ) {
+            return tile;
+        }
+
+        let near_water = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+            .into_iter()
+            .any(|(nx, ny)| self.elevation_at(nx, ny) < SEA_LEVEL);
+        if near_water { TerrainTile::Coast } else { tile }
+    }
 
-        for _ in 0..resource_spots as u64 {
-            let x = self.rng.random_range(0..world.get_width()) as i32;
-            let y = self.rng.random_range(0..world.get_height()) as i32;
+    // Истоки рек чанка: несколько случайных клеток, если они в горах
+    fn river_sources(&self, position: (i32, i32)) -> Vec<(i32, i32)> {
+        let mut rng = StdRng::seed_from_u64(chunk_seed(self.seed, position, RIVER_SALT));
+        let (x0, y0) = chunk_origin(position);
 
-            // Проверяем, что это не вода
-            if let Some(TerrainTile::Land) = world.get_tile(x, y) {
-                // Здесь можно расширить: добавить enum с типами ресурсов в TerrainTile
-                // и генерировать разные ресурсы (например, дерево, камень, золото)
-                world.set_tile(x, y, TerrainTile::ResourceSpot(ResourceType::default()));
+        (0..RIVER_ATTEMPTS_PER_CHUNK)
+            .map(|_| {
+                (
+                    x0 + rng.random_range(0..CHUNK_SIZE),
+                    y0 + rng.random_range(0..CHUNK_SIZE),
+                )
+            })
+            .filter(|(x, y)| self.biome_at(*x, *y) == TerrainTile::Mountain)
+            .collect()
+    }
+
+    // Русло реки по самому крутому спуску, пока она не достигнет воды. Реки
+    // с общим участком дальше текут одним руслом, а в низине без стока река
+    // заканчивается
+    fn trace_river(&self, source: (i32, i32)) -> Vec<(i32, i32)> {
+        let mut path = Vec::new();
+        let mut current = source;
+        let mut height = self.elevation_at(source.0, source.1);
+
+        for _ in 0..MAX_RIVER_LENGTH {
+            let (next, next_height) = neighbors(current)
+                .into_iter()
+                .map(|(x, y)| ((x, y), self.elevation_at(x, y)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .expect("у клетки всегда есть соседи");
+
+            if next_height >= height || next_height < SEA_LEVEL {
+                break;
             }
+            path.push(next);
+            current = next;
+            height = next_height;
         }
+
+        path
+    }
+
+    // Месторождения зависят от биома: железо, камень и кристаллы в горах,
+    // лес в лесах и на болотах. Лес восстанавливается, недра - нет
+    fn generate_deposits(&self, position: (i32, i32), chunk: &Chunk) -> Vec<Deposit> {
+        let mut rng = StdRng::seed_from_u64(chunk_seed(self.seed, position, DEPOSIT_SALT));
+        let (x0, y0) = chunk_origin(position);
+        let mut deposits = Vec::new();
+
+        for x in x0..x0 + CHUNK_SIZE {
+            for y in y0..y0 + CHUNK_SIZE {
+                let candidates = deposit_candidates(chunk.get(x, y));
+                if candidates.is_empty() || !rng.random_bool(DEPOSIT_CHANCE) {
+                    continue;
+                }
+
+                let roll = rng.random_range(0..100);
+                let mut threshold = 0;
+                for (resource, chance, amount, regeneration) in candidates {
+                    threshold += chance;
+                    if roll < threshold {
+                        let amount = rng.random_range(amount.0..=amount.1);
+                        deposits.push(Deposit::new((x, y), resource, amount, regeneration));
+                        break;
+                    }
+                }
+            }
+        }
+
+        deposits
+    }
+}
+
+// Слой шума рельефа или влажности
+fn noise_layer(seed: u64) -> Fbm<Perlin> {
+    Fbm::<Perlin>::new((seed ^ (seed >> 32)) as u32)
+        .set_octaves(TERRAIN_OCTAVES)
+        .set_frequency(1.0 / TERRAIN_SCALE)
+}
+
+// Значение шума в клетке от 0.0 до 1.0
+fn sample(noise: &Fbm<Perlin>, x: i32, y: i32) -> f64 {
+    ((noise.get([x as f64, y as f64]) + 1.0) / 2.0).clamp(0.0, 1.0)
+}
+
+// Зерно случайного потока чанка. Перемешивание в духе SplitMix64, чтобы
+// соседние чанки получали несвязанные последовательности
+fn chunk_seed(seed: u64, (x, y): (i32, i32), salt: u64) -> u64 {
+    let mut hash = seed ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    hash ^= (x as u32 as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = hash.rotate_left(31) ^ (y as u32 as u64).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^ (hash >> 31)
+}
+
+// Мировые координаты левого верхнего угла чанка
+fn chunk_origin((x, y): (i32, i32)) -> (i32, i32) {
+    (x * CHUNK_SIZE, y * CHUNK_SIZE)
+}
+
+fn world_contains(width: u64, height: u64, x: i32, y: i32) -> bool {
+    x >= 0 && (x as i64) < width as i64 && y >= 0 && (y as i64) < height as i64
+}
+
+// Биом клетки по ее высоте и влажности
+fn biome(elevation: f64, moisture: f64) -> TerrainTile {
+    if elevation < SEA_LEVEL {
+        TerrainTile::Water
+    } else if elevation >= SNOW_LEVEL {
+        TerrainTile::Snow
+    } else if elevation >= MOUNTAIN_LEVEL {
+        TerrainTile::Mountain
+    } else if moisture < DESERT_MOISTURE {
+        TerrainTile::Desert
+    } else if moisture >= SWAMP_MOISTURE && elevation < SWAMP_MAX_ELEVATION {
+        TerrainTile::Swamp
+    } else if moisture >= FOREST_MOISTURE {
+        TerrainTile::Forest
+    } else {
+        TerrainTile::Land
+    }
+}
+
+// Возможные месторождения биома: ресурс, шанс в процентах (в сумме 100),
+// диапазон запаса и восстановление за тик
+fn deposit_candidates(tile: &TerrainTile) -> Vec<(ResourceType, u32, (u32, u32), u32)> {
+    match tile {
+        TerrainTile::Mountain => vec![
+            (ResourceType::Iron, 50, (800, 2000), 0),
+            (ResourceType::Stone, 30, (1500, 4000), 0),
+            (ResourceType::Crystal, 20, (200, 600), 0),
+        ],
+        TerrainTile::Snow => vec![(ResourceType::Crystal, 100, (300, 900), 0)],
+        TerrainTile::Desert => vec![(ResourceType::Stone, 100, (1000, 3000), 0)],
+        TerrainTile::Forest => vec![(ResourceType::Wood, 100, (500, 1500), 5)],
+        TerrainTile::Swamp => vec![(ResourceType::Wood, 100, (300, 800), 3)],
+        _ => Vec::new(),
     }
 }
 
+// Соседние клетки, включая диагонали
+fn neighbors((x, y): (i32, i32)) -> [(i32, i32); 8] {
+    [
+        (x - 1, y - 1),
+        (x, y - 1),
+        (x + 1, y - 1),
+        (x - 1, y),
+        (x + 1, y),
+        (x - 1, y + 1),
+        (x, y + 1),
+        (x + 1, y + 1),
+    ]
+}
+
+// Месторождение ресурса на карте мира
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Deposit {
+    pub position: (i32, i32),
+    pub resource: ResourceType,
+    pub amount: u32,       // Оставшийся запас
+    pub capacity: u32,     // Начальный запас
+    pub regeneration: u32, // Восстановление за тик, 0 - месторождение исчерпаемо
+}
+
+impl Deposit {
+    pub fn new(
+        position: (i32, i32),
+        resource: ResourceType,
+        amount: u32,
+        regeneration: u32,
+    ) -> Self {
+        Deposit {
+            position,
+            resource,
+            amount,
+            capacity: amount,
+            regeneration,
+        }
+    }
+
+    pub fn is_depleted(&self) -> bool {
+        self.amount == 0
+    }
+
+    pub fn is_renewable(&self) -> bool {
+        self.regeneration > 0
+    }
+
+    // Добывает не больше, чем осталось. Возвращает добытое
+    pub fn extract(&mut self, amount: u32) -> u32 {
+        let extracted = self.amount.min(amount);
+        self.amount -= extracted;
+        extracted
+    }
+
+    // Расстояние до позиции в клетках, диагональ считается за одну клетку
+    pub fn distance(&self, position: (i32, i32)) -> i32 {
+        (self.position.0 - position.0)
+            .abs()
+            .max((self.position.1 - position.1).abs())
+    }
+
+    pub fn is_near(&self, position: (i32, i32), radius: i32) -> bool {
+        self.distance(position) <= radius
+    }
+
+    pub fn regenerate(&mut self) {
+        self.amount = self
+            .amount
+            .saturating_add(self.regeneration)
+            .min(self.capacity);
+    }
+}
+
+// Добывает ресурс из месторождений не дальше `radius` клеток от позиции,
+// начиная с ближайших, пока не наберется `amount`. Возвращает добытое
+pub fn extract_from(
+    deposits: &mut [Deposit],
+    resource: &ResourceType,
+    position: (i32, i32),
+    radius: i32,
+    amount: u32,
+) -> u32 {
+    let mut nearby: Vec<&mut Deposit> = deposits
+        .iter_mut()
+        .filter(|d| &d.resource == resource && d.is_near(position, radius))
+        .collect();
+    nearby.sort_by_key(|d| (d.distance(position), d.position));
+
+    let mut extracted = 0;
+    for deposit in nearby {
+        if extracted == amount {
+            break;
+        }
+        extracted += deposit.extract(amount - extracted);
+    }
+    extracted
+}
+
+// Карта хранится чанками по CHUNK_SIZE x CHUNK_SIZE клеток, по байту на
+// клетку. Чанк создается при первой записи, до этого все его клетки - Land.
+// Безграничная карта не имеет размеров, и ее несозданные чанки - Unknown.
+// Здания и города лежат поверх чанков в отдельном слое
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorldMap {
     width: u64,
     height: u64,
-    terrain: HashMap<(i32, i32), TerrainTile>,
+    chunks: HashMap<(i32, i32), Chunk>,
+    overlay: HashMap<(i32, i32), TerrainTile>,
+    #[serde(default)]
+    deposits: HashMap<(i32, i32), Deposit>, // Месторождения, еще не занятые городами
+    #[serde(default = "bounded")]
+    bounded: bool,
+}
+
+fn bounded() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -96,30 +454,64 @@ pub enum TerrainTile {
     Mountain,
     Forest,
     Desert,
-    Building(String),           // Имя здания
-    ResourceSpot(ResourceType), // Месторождение ресурса
-    City(String),               // Имя города
+    Swamp,
+    Snow,
+    River,
+    Coast,
+    Building(String), // Имя здания
+    City(String),     // Имя города
     Unknown,
 }
 
-impl WorldMap {
-    pub fn new(width: u64, height: u64) -> WorldMap {
-        let mut terrain = HashMap::new();
+impl TerrainTile {
+    pub fn is_water(&self) -> bool {
+        matches!(self, TerrainTile::Water | TerrainTile::River)
+    }
 
-        // Заполнение карты начальными значениями
-        for x in 0..width as i32 {
-            for y in 0..height as i32 {
-                terrain.insert((x, y), TerrainTile::Land);
-            }
+    // Местность города, основанного на этой клетке
+    pub fn city_terrain(&self) -> Option<Terrain> {
+        match self {
+            TerrainTile::Land => Some(Terrain::Plain),
+            TerrainTile::Forest => Some(Terrain::Forest),
+            TerrainTile::Mountain => Some(Terrain::Mountain),
+            TerrainTile::Desert => Some(Terrain::Desert),
+            TerrainTile::Swamp => Some(Terrain::Swamp),
+            TerrainTile::Snow => Some(Terrain::Snow),
+            TerrainTile::Coast => Some(Terrain::Water),
+            TerrainTile::Water
+            | TerrainTile::River
+            | TerrainTile::Building(_)
+            | TerrainTile::City(_)
+            | TerrainTile::Unknown => None,
         }
+    }
+}
 
+impl WorldMap {
+    pub fn new(width: u64, height: u64) -> WorldMap {
         WorldMap {
             width,
             height,
-            terrain,
+            chunks: HashMap::new(),
+            overlay: HashMap::new(),
+            deposits: HashMap::new(),
+            bounded: true,
+        }
+    }
+
+    // Карта, которая растет по мере исследования. Чанки в нее добавляет
+    // WorldGenerator::explore
+    pub fn unbounded() -> WorldMap {
+        WorldMap {
+            bounded: false,
+            ..WorldMap::new(0, 0)
         }
     }
 
+    pub fn is_bounded(&self) -> bool {
+        self.bounded
+    }
+
     pub fn get_width(&self) -> u64 {
         self.width
     }
@@ -128,45 +520,115 @@ impl WorldMap {
         self.height
     }
 
-    pub fn expand_map(&mut self, new_width: u64, new_height: u64) {
-        for x in self.width..new_width {
-            for y in 0..self.height {
-                self.terrain
-                    .insert((x as i32, y as i32), TerrainTile::Unknown);
-            }
+    // Меняет размеры ограниченной карты, не трогая клетки
+    pub fn resize(&mut self, width: u64, height: u64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn is_in_bounds(&self, x: i32, y: i32) -> bool {
+        !self.bounded || world_contains(self.width, self.height, x, y)
+    }
+
+    pub fn set_tile(&mut self, x: i32, y: i32, tile: TerrainTile) {
+        if !self.is_in_bounds(x, y) {
+            return;
         }
 
-        for y in self.height..new_height {
-            for x in 0..new_width {
-                self.terrain
-                    .insert((x as i32, y as i32), TerrainTile::Unknown);
+        match tile {
+            TerrainTile::Building(_) | TerrainTile::City(_) => {
+                self.overlay.insert((x, y), tile);
+            }
+            _ => {
+                let unloaded = self.unloaded_tile();
+                self.overlay.remove(&(x, y));
+                self.chunks
+                    .entry(chunk::chunk_position(x, y))
+                    .or_insert_with(|| Chunk::filled(unloaded))
+                    .set(x, y, &tile);
             }
         }
+    }
+
+    pub fn get_tile(&self, x: i32, y: i32) -> Option<&TerrainTile> {
+        if !self.is_in_bounds(x, y) {
+            return None;
+        }
+        if let Some(tile) = self.overlay.get(&(x, y)) {
+            return Some(tile);
+        }
 
-        self.width = new_width;
-        self.height = new_height;
+        Some(match self.chunks.get(&chunk::chunk_position(x, y)) {
+            Some(chunk) => chunk.get(x, y),
+            None => self.unloaded_tile(),
+        })
     }
 
-    pub fn set_tile(&mut self, x: i32, y: i32, tile: TerrainTile) {
-        if x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32 {
-            self.terrain.insert((x, y), tile);
+    // Клетка несозданного чанка
+    fn unloaded_tile(&self) -> &'static TerrainTile {
+        if self.bounded {
+            &TerrainTile::Land
+        } else {
+            &TerrainTile::Unknown
         }
     }
 
-    pub fn get_tile(&self, x: i32, y: i32) -> Option<&TerrainTile> {
-        self.terrain.get(&(x, y))
+    // Сколько чанков уже создано в памяти
+    pub fn loaded_chunks(&self) -> usize {
+        self.chunks.len()
     }
 
-    pub fn add_building(&mut self, x: i32, y: i32, building_name: String) {
-        if let Some(_) = self.terrain.get(&(x, y)) {
-            self.terrain
-                .insert((x, y), TerrainTile::Building(building_name));
+    pub fn is_chunk_loaded(&self, position: (i32, i32)) -> bool {
+        self.chunks.contains_key(&position)
+    }
+
+    // Добавляет сгенерированный чанк вместе с его месторождениями
+    pub fn insert_chunk(&mut self, generated: GeneratedChunk) {
+        self.chunks.insert(generated.position, generated.chunk);
+        for deposit in generated.deposits {
+            if self.is_in_bounds(deposit.position.0, deposit.position.1) {
+                self.add_deposit(deposit);
+            }
         }
     }
 
+    pub fn add_deposit(&mut self, deposit: Deposit) {
+        self.deposits.insert(deposit.position, deposit);
+    }
+
+    pub fn get_deposit(&self, x: i32, y: i32) -> Option<&Deposit> {
+        self.deposits.get(&(x, y))
+    }
+
+    // Свободные месторождения не дальше `radius` клеток от позиции
+    pub fn deposits_near(&self, position: (i32, i32), radius: i32) -> Vec<&Deposit> {
+        let mut deposits: Vec<&Deposit> = self
+            .deposits
+            .values()
+            .filter(|deposit| deposit.is_near(position, radius))
+            .collect();
+        deposits.sort_by_key(|deposit| deposit.position);
+        deposits
+    }
+
+    // Забирает месторождения вокруг позиции, например для основанного города
+    pub fn claim_deposits(&mut self, position: (i32, i32), radius: i32) -> Vec<Deposit> {
+        let positions: Vec<(i32, i32)> = self
+            .deposits_near(position, radius)
+            .iter()
+            .map(|deposit| deposit.position)
+            .collect();
+        positions
+            .into_iter()
+            .filter_map(|position| self.deposits.remove(&position))
+            .collect()
+    }
+
+    pub fn add_building(&mut self, x: i32, y: i32, building_name: String) {
+        self.set_tile(x, y, TerrainTile::Building(building_name));
+    }
+
     pub fn add_city(&mut self, x: i32, y: i32, city_name: String) {
-        if let Some(_) = self.terrain.get(&(x, y)) {
-            self.terrain.insert((x, y), TerrainTile::City(city_name));
-        }
+        self.set_tile(x, y, TerrainTile::City(city_name));
     }
 }
@@ -1,6 +1,16 @@
 mod i18n;
 
 use anyhow::{Context, Result};
+use cityrade_types::{
+    building::BuildingType,
+    city::{City, Terrain},
+    construction::BuildQueue,
+    content,
+    game::{Command, Game},
+    production::ProductionBreakdown,
+    resources::ResourceType,
+    technology::{ResearchState, ResearchStatus, TechnologyType},
+};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
     execute,
@@ -14,13 +24,21 @@ use ratatui::{
     text::{Line, Span, Text},
     widgets::{Block, Borders, List, ListItem, Paragraph, Tabs, Wrap},
 };
-use std::{io::stdout, time::Duration};
+use std::{
+    io::stdout,
+    time::{Duration, Instant},
+};
 use tokio::{sync::mpsc, task};
 
 #[derive(Default)]
 struct GameState {
     resources: Option<cityrade_types::resources::Resources>,
+    production: Option<ProductionBreakdown>,
+    resource_cursor: usize,
+    expanded_resources: Vec<ResourceType>,
     buildings: Vec<String>,
+    construction: Option<BuildQueue>,
+    research: Option<ResearchState>,
     chat_messages: Vec<String>,
     current_tab: usize,
     input: String,
@@ -40,6 +58,26 @@ enum ConnectionStatus {
     Error(String),
 }
 
+const GAME_TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+const RESOURCE_ORDER: [ResourceType; 7] = [
+    ResourceType::Gold,
+    ResourceType::Wood,
+    ResourceType::Stone,
+    ResourceType::Food,
+    ResourceType::Iron,
+    ResourceType::Crystal,
+    ResourceType::Energy,
+];
+
+fn rate_style(rate: i32) -> Style {
+    match rate {
+        r if r > 0 => Style::default().fg(Color::Green),
+        r if r < 0 => Style::default().fg(Color::Red),
+        _ => Style::default().fg(Color::Gray),
+    }
+}
+
 #[derive(PartialEq)]
 enum InputMode {
     Normal,
@@ -48,15 +86,16 @@ enum InputMode {
 
 enum Message {
     ServerMessage(String),
-    GameUpdate {
-        resources: Option<cityrade_types::resources::Resources>,
-    },
+    LoggedIn(String),
     ConnectionStatus(ConnectionStatus),
 }
 
 pub struct App {
     state: GameState,
     input_mode: InputMode,
+    game: Game,
+    player: Option<String>,
+    city: Option<String>,
     tx: mpsc::Sender<Message>,
     rx: mpsc::Receiver<Message>,
     should_quit: bool,
@@ -68,6 +107,9 @@ impl App {
         Self {
             state: GameState::default(),
             input_mode: InputMode::Normal,
+            game: Game::new(0),
+            player: None,
+            city: None,
             tx,
             rx,
             should_quit: false,
@@ -87,10 +129,27 @@ impl App {
                     self.input_mode = InputMode::Editing;
                 }
                 KeyCode::Tab => {
-                    self.state.current_tab = (self.state.current_tab + 1) % 4;
+                    self.state.current_tab = (self.state.current_tab + 1) % 5;
                 }
                 KeyCode::BackTab => {
-                    self.state.current_tab = (self.state.current_tab + 3) % 4;
+                    self.state.current_tab = (self.state.current_tab + 4) % 5;
+                }
+                KeyCode::Up if self.state.current_tab == 0 => {
+                    self.state.resource_cursor = self.state.resource_cursor.saturating_sub(1);
+                }
+                KeyCode::Down if self.state.current_tab == 0 => {
+                    self.state.resource_cursor =
+                        (self.state.resource_cursor + 1).min(RESOURCE_ORDER.len() - 1);
+                }
+                KeyCode::Enter if self.state.current_tab == 0 => {
+                    let resource = &RESOURCE_ORDER[self.state.resource_cursor];
+                    let expanded = &mut self.state.expanded_resources;
+                    match expanded.iter().position(|r| r == resource) {
+                        Some(index) => {
+                            expanded.remove(index);
+                        }
+                        None => expanded.push(resource.clone()),
+                    }
                 }
                 _ => {}
             },
@@ -159,11 +218,25 @@ impl App {
                 self.login(username, password)?;
             }
             "build" => {
-                if parts.len() < 4 {
+                if parts.len() < 5 {
                     self.log("Usage: build <name> <type> <x> <y>");
                     return Ok(());
                 }
-                self.log("Building functionality not yet implemented");
+                self.build(parts[1], parts[2], parts[3], parts[4])?;
+            }
+            "cancel" => {
+                if parts.len() < 2 {
+                    self.log("Usage: cancel <number>");
+                    return Ok(());
+                }
+                self.cancel(parts[1])?;
+            }
+            "research" => {
+                if parts.len() < 2 {
+                    self.log("Usage: research <tech>");
+                    return Ok(());
+                }
+                self.research(&parts[1..].join(" "))?;
             }
             "chat" => {
                 if parts.len() < 2 {
@@ -181,12 +254,15 @@ impl App {
                 self.log("  connect <server>                - Connect to server");
                 self.log("  login <username> <password>     - Login to server");
                 self.log("  build <name> <type> <x> <y>     - Build a building");
+                self.log("  cancel <number>                 - Cancel a construction project");
+                self.log("  research <tech>                 - Queue a technology for research");
                 self.log("  chat <message>                  - Send chat message");
                 self.log("  help                            - Show this help");
                 self.log("  quit, exit                      - Exit the game");
                 self.log("");
                 self.log("Keyboard shortcuts:");
                 self.log("  Tab/Shift+Tab - Switch between tabs");
+                self.log("  Up/Down, Enter - Select and expand a resource (Resources tab)");
                 self.log("  'e' - Enter edit mode");
                 self.log("  'q' - Quit (in normal mode)");
                 self.log("  Esc - Exit edit mode");
@@ -241,8 +317,7 @@ impl App {
                             username
                         )))
                         .await;
-                    let resources = Some(cityrade_types::resources::Resources::new());
-                    let _ = tx.send(Message::GameUpdate { resources }).await;
+                    let _ = tx.send(Message::LoggedIn(username)).await;
                 });
             }
             _ => {
@@ -252,6 +327,144 @@ impl App {
         Ok(())
     }
 
+    fn join(&mut self, username: String) {
+        let name = format!("{}'s city", username);
+        if !self.game.cities.contains_key(&name) {
+            let mut city = City::new(name.clone(), username.clone(), Terrain::Plain, (0, 0));
+            city.update_resource_production();
+            if let Err(e) = self.game.add_city(city) {
+                self.log(&e);
+                return;
+            }
+        }
+        self.player = Some(username);
+        self.city = Some(name);
+        self.sync();
+        self.log("Received updated resource data");
+    }
+
+    fn sync(&mut self) {
+        let Some(city) = self
+            .city
+            .as_ref()
+            .and_then(|name| self.game.cities.get(name))
+        else {
+            return;
+        };
+        let mut buildings: Vec<_> = city.buildings.values().collect();
+        buildings.sort_by(|a, b| a.name.cmp(&b.name));
+        self.state.buildings = buildings
+            .into_iter()
+            .map(|b| format!("{} ({:?}, level {})", b.name, b.building_type, b.level))
+            .collect();
+        self.state.resources = Some(city.resources.clone());
+        self.state.production = Some(city.production.clone());
+        self.state.construction = Some(city.construction.clone());
+        self.state.research = Some(city.research.clone());
+    }
+
+    fn execute(&mut self, command: Command) -> Option<String> {
+        let Some(player) = self.player.clone() else {
+            self.log("Not logged in. Use 'login <username> <password>' first.");
+            return None;
+        };
+        match self.game.execute(&player, command) {
+            Ok(id) => {
+                self.sync();
+                Some(id)
+            }
+            Err(e) => {
+                self.log(&e);
+                None
+            }
+        }
+    }
+
+    fn tick(&mut self) {
+        for event in self.game.tick() {
+            self.log(&event.to_string());
+        }
+        self.sync();
+    }
+
+    fn build(&mut self, name: &str, building_type: &str, x: &str, y: &str) -> Result<()> {
+        let building_type = match building_type.parse::<BuildingType>() {
+            Ok(building_type) => building_type,
+            Err(e) => {
+                self.log(&e);
+                return Ok(());
+            }
+        };
+        let (Ok(x), Ok(y)) = (x.parse::<i32>(), y.parse::<i32>()) else {
+            self.log("Coordinates must be integers");
+            return Ok(());
+        };
+        let Some(city) = self.city.clone() else {
+            self.log("Not logged in. Use 'login <username> <password>' first.");
+            return Ok(());
+        };
+        let command = Command::Build {
+            city,
+            building_type: building_type.clone(),
+            name: name.to_string(),
+            position: (x, y),
+        };
+        if self.execute(command).is_some() {
+            self.log(&format!(
+                "Construction queued: {} ({:?})",
+                name, building_type
+            ));
+        }
+        Ok(())
+    }
+
+    // Отменяет проект по его номеру в очереди строительства
+    fn cancel(&mut self, number: &str) -> Result<()> {
+        let (Some(city), Some(construction)) = (self.city.clone(), &self.state.construction) else {
+            self.log("Not logged in. Use 'login <username> <password>' first.");
+            return Ok(());
+        };
+        let project = number
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|index| construction.projects.get(index));
+        let Some(project) = project else {
+            self.log("No such construction project");
+            return Ok(());
+        };
+        let command = Command::CancelConstruction {
+            city,
+            project_id: project.id.clone(),
+        };
+        if self.execute(command).is_some() {
+            self.log(&format!("Construction cancelled: {}", number));
+        }
+        Ok(())
+    }
+
+    fn research(&mut self, tech: &str) -> Result<()> {
+        let tech_type = match tech.parse::<TechnologyType>() {
+            Ok(tech_type) => tech_type,
+            Err(e) => {
+                self.log(&e);
+                return Ok(());
+            }
+        };
+        let Some(city) = self.city.clone() else {
+            self.log("Not logged in. Use 'login <username> <password>' first.");
+            return Ok(());
+        };
+        let command = Command::StartResearch {
+            city,
+            technology: tech_type.clone(),
+        };
+        if self.execute(command).is_some() {
+            self.log(&format!("Research queued: {:?}", tech_type));
+        }
+        Ok(())
+    }
+
     fn send_chat_message(&mut self, message: &str) -> Result<()> {
         match self.state.connection_status {
             ConnectionStatus::Connected => {
@@ -279,15 +492,12 @@ impl App {
         while let Ok(message) = self.rx.try_recv() {
             match message {
                 Message::ServerMessage(msg) => {
-                    if self.state.current_tab == 2 {
+                    if self.state.current_tab == 3 {
                         self.state.chat_messages.push(msg.clone());
                     }
                     self.log(&msg);
                 }
-                Message::GameUpdate { resources } => {
-                    self.state.resources = resources;
-                    self.log("Received updated resource data");
-                }
+                Message::LoggedIn(username) => self.join(username),
                 Message::ConnectionStatus(status) => {
                     self.state.connection_status = status;
                 }
@@ -306,15 +516,16 @@ impl App {
                 Constraint::Length(3),
             ])
             .split(f.area());
-        let titles = ["Resources", "Buildings", "Chat", "Logs"]
+        let titles = ["Resources", "Buildings", "Research", "Chat", "Logs"]
             .iter()
             .map(|t| {
                 let selected = self.state.current_tab
                     == match *t {
                         "Resources" => 0,
                         "Buildings" => 1,
-                        "Chat" => 2,
-                        "Logs" => 3,
+                        "Research" => 2,
+                        "Chat" => 3,
+                        "Logs" => 4,
                         _ => 0,
                     };
                 if selected {
@@ -341,8 +552,9 @@ impl App {
         match self.state.current_tab {
             0 => self.draw_resources(f, chunks[1]),
             1 => self.draw_buildings(f, chunks[1]),
-            2 => self.draw_chat(f, chunks[1]),
-            3 => self.draw_logs(f, chunks[1]),
+            2 => self.draw_research(f, chunks[1]),
+            3 => self.draw_chat(f, chunks[1]),
+            4 => self.draw_logs(f, chunks[1]),
             _ => {}
         }
         let status = match &self.state.connection_status {
@@ -386,43 +598,59 @@ impl App {
         let inner = block.inner(area);
         f.render_widget(block, area);
         if let Some(resources) = &self.state.resources {
-            let resources_text = Text::from(vec![
+            let mut lines = vec![
                 Line::from(Span::styled(
-                    "City Resources",
+                    "City Resources (Enter - show sources)",
                     Style::default().add_modifier(Modifier::BOLD),
                 )),
                 Line::from(""),
-                Line::from(vec![
-                    Span::raw("Gold: "),
-                    Span::styled("100", Style::default().fg(Color::Yellow)),
-                ]),
-                Line::from(vec![
-                    Span::raw("Wood: "),
-                    Span::styled("200", Style::default().fg(Color::Green)),
-                ]),
-                Line::from(vec![
-                    Span::raw("Stone: "),
-                    Span::styled("300", Style::default().fg(Color::Gray)),
-                ]),
-                Line::from(""),
-                Line::from(Span::styled(
-                    "Production per minute:",
-                    Style::default().add_modifier(Modifier::BOLD),
-                )),
-                Line::from(vec![
-                    Span::raw("Gold: "),
-                    Span::styled("+5", Style::default().fg(Color::Yellow)),
-                ]),
-                Line::from(vec![
-                    Span::raw("Wood: "),
-                    Span::styled("+10", Style::default().fg(Color::Green)),
-                ]),
-                Line::from(vec![
-                    Span::raw("Stone: "),
-                    Span::styled("+8", Style::default().fg(Color::Gray)),
-                ]),
-            ]);
-            let resource_para = Paragraph::new(resources_text).wrap(Wrap { trim: true });
+            ];
+            for (i, resource) in RESOURCE_ORDER.iter().enumerate() {
+                let expanded = self.state.expanded_resources.contains(resource);
+                let rate = resources.get_production_rate(resource);
+                let selected = i == self.state.resource_cursor;
+                let label_style = if selected {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "{} {}: {}",
+                            if expanded { "v" } else { ">" },
+                            resource,
+                            resources.get(resource)
+                        ),
+                        label_style,
+                    ),
+                    Span::styled(format!(" ({:+}/tick)", rate), rate_style(rate)),
+                ]));
+                if !expanded {
+                    continue;
+                }
+                let sources = self
+                    .state
+                    .production
+                    .as_ref()
+                    .map(|production| production.sources(resource))
+                    .unwrap_or(&[]);
+                if sources.is_empty() {
+                    lines.push(Line::from(Span::styled(
+                        "    no production",
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                }
+                for (source, amount) in sources {
+                    lines.push(Line::from(vec![
+                        Span::raw(format!("    {}: ", source)),
+                        Span::styled(format!("{:+}", amount), rate_style(*amount)),
+                    ]));
+                }
+            }
+            let resource_para = Paragraph::new(Text::from(lines)).wrap(Wrap { trim: false });
             f.render_widget(resource_para, inner);
         } else {
             let text =
@@ -439,6 +667,10 @@ impl App {
             .border_style(Style::default().fg(Color::Cyan));
         let inner = block.inner(area);
         f.render_widget(block, area);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(inner);
         if self.state.buildings.is_empty() {
             let text = if self.state.connection_status == ConnectionStatus::Connected {
                 "No buildings. Use 'build' command to create one."
@@ -446,7 +678,7 @@ impl App {
                 "Not connected. Connect to server first."
             };
             let para = Paragraph::new(text).style(Style::default().fg(Color::Gray));
-            f.render_widget(para, inner);
+            f.render_widget(para, rows[0]);
         } else {
             let items: Vec<ListItem> = self
                 .state
@@ -455,7 +687,103 @@ impl App {
                 .map(|b| ListItem::new(b.clone()))
                 .collect();
             let list = List::new(items).highlight_style(Style::default().fg(Color::Yellow));
-            f.render_widget(list, inner);
+            f.render_widget(list, rows[0]);
+        }
+        let Some(construction) = &self.state.construction else {
+            return;
+        };
+        let active = construction.active().len();
+        let items: Vec<ListItem> = construction
+            .projects
+            .iter()
+            .enumerate()
+            .map(|(i, project)| {
+                let filled = (project.progress_ratio() * 10.0).round() as usize;
+                let label = format!(
+                    "{}. [{}{}] {}/{} {}",
+                    i + 1,
+                    "#".repeat(filled),
+                    ".".repeat(10 - filled),
+                    project.progress,
+                    project.total_ticks,
+                    project.describe()
+                );
+                let style = if i < active {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                };
+                ListItem::new(label).style(style)
+            })
+            .collect();
+        let list = List::new(items).block(
+            Block::default()
+                .title(format!(
+                    "Construction ({}/{} crews busy)",
+                    active, construction.slots
+                ))
+                .borders(Borders::TOP),
+        );
+        f.render_widget(list, rows[1]);
+    }
+
+    fn draw_research(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .title("Research")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let Some(research) = &self.state.research else {
+            let text =
+                Paragraph::new("No research data available.\nConnect to a server and login first.")
+                    .style(Style::default().fg(Color::Gray));
+            f.render_widget(text, inner);
+            return;
+        };
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(34),
+                Constraint::Percentage(33),
+                Constraint::Percentage(33),
+            ])
+            .split(inner);
+        let mut available = Vec::new();
+        let mut locked = Vec::new();
+        let mut completed = Vec::new();
+        let technologies = &content::current().technologies;
+        for tech_type in TechnologyType::all() {
+            let Some(tech) = technologies.get_technology(&tech_type) else {
+                continue;
+            };
+            let status = research.status(technologies, &tech_type);
+            let (label, style) = match status {
+                ResearchStatus::InProgress => (
+                    format!("{} ({}/{})", tech.name, research.progress, tech.cost),
+                    Style::default().fg(Color::Yellow),
+                ),
+                ResearchStatus::Queued => (
+                    format!("{} (queued)", tech.name),
+                    Style::default().fg(Color::Yellow),
+                ),
+                ResearchStatus::Locked => (tech.name.clone(), Style::default().fg(Color::DarkGray)),
+                _ => (format!("{} ({})", tech.name, tech.cost), Style::default()),
+            };
+            let item = ListItem::new(Span::styled(label, style));
+            match status {
+                ResearchStatus::Completed => completed.push(item),
+                ResearchStatus::Locked => locked.push(item),
+                _ => available.push(item),
+            }
+        }
+        for (items, title, area) in [
+            (available, "Available", columns[0]),
+            (locked, "Locked", columns[1]),
+            (completed, "Completed", columns[2]),
+        ] {
+            let list = List::new(items).block(Block::default().title(title).borders(Borders::TOP));
+            f.render_widget(list, area);
         }
     }
 
@@ -536,9 +864,14 @@ pub async fn run() -> Result<()> {
     let mut app = App::new();
     app.log("Welcome to Cityrade!");
     app.log("Type 'help' for a list of commands");
+    let mut last_tick = Instant::now();
     loop {
         terminal.draw(|f| app.draw(f))?;
         app.process_messages()?;
+        if last_tick.elapsed() >= GAME_TICK_INTERVAL {
+            app.tick();
+            last_tick = Instant::now();
+        }
         if app.should_quit {
             break;
         }
@@ -2,6 +2,7 @@ mod client;
 mod server;
 
 use clap::{Parser, arg};
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 #[command(name = "cityrade", version, about, long_about)]
@@ -10,6 +11,9 @@ struct Args {
     serve: bool,
     #[arg(short, long)]
     web: bool,
+    /// Directory with content pack files (*.ron, *.toml), reloaded on change
+    #[arg(long)]
+    content: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -17,7 +21,7 @@ async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
     let mode = match (args.serve, args.web) {
-        (true, false) => server::serve().await,
+        (true, false) => server::serve(args.content).await,
         // (false, true) => client::web().await,
         (false, false) => client::run().await,
         _ => anyhow::bail!("Serving in web? Really?"),
@@ -1,5 +1,83 @@
 use anyhow::Result;
+use cityrade_types::{content::ContentWatcher, game::Game};
+use futures_util::SinkExt;
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::broadcast::{self, error::RecvError},
+};
+use tokio_tungstenite::tungstenite::Message;
 
-pub async fn serve() -> Result<()> {
-    Ok(())
+const SERVER_ADDR: &str = "0.0.0.0:9001";
+const CONTENT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const GAME_TICK_INTERVAL: Duration = Duration::from_secs(1);
+// Сколько событий ждут отправки медленному клиенту, прежде чем он начнет их терять
+const EVENT_BUFFER: usize = 256;
+
+pub async fn serve(content_dir: Option<PathBuf>) -> Result<()> {
+    // Первая загрузка должна пройти успешно, иначе сервер не стартует
+    let mut watcher = content_dir.map(ContentWatcher::new);
+    if let Some(watcher) = watcher.as_mut() {
+        watcher.poll().map_err(anyhow::Error::msg)?;
+    }
+
+    // Зерно мира берется из текущего времени запуска
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    let mut game = Game::new(seed);
+
+    // Каждый подключенный клиент получает игровые события в формате JSON
+    let listener = TcpListener::bind(SERVER_ADDR).await?;
+    let (events, _) = broadcast::channel::<String>(EVENT_BUFFER);
+    let mut ticks = tokio::time::interval(GAME_TICK_INTERVAL);
+    let mut content_polls = tokio::time::interval(CONTENT_POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                if let Ok((stream, _)) = accepted {
+                    tokio::spawn(send_events(stream, events.subscribe()));
+                }
+            }
+            _ = ticks.tick() => {
+                for event in game.tick() {
+                    // Ошибка отправки значит только, что клиентов сейчас нет
+                    let _ = events.send(serde_json::to_string(&event)?);
+                }
+            }
+            _ = content_polls.tick(), if watcher.is_some() => {
+                let Some(watcher) = watcher.as_mut() else {
+                    continue;
+                };
+                match watcher.poll() {
+                    Ok(true) => println!("Контент перезагружен"),
+                    Ok(false) => {}
+                    Err(e) => eprintln!("Не удалось перезагрузить контент, остается прежний: {}", e),
+                }
+            }
+        }
+    }
+}
+
+// Пересылает события игры клиенту, пока соединение не закроется
+async fn send_events(stream: TcpStream, mut events: broadcast::Receiver<String>) {
+    let Ok(mut socket) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if socket.send(Message::text(event)).await.is_err() {
+                    return;
+                }
+            }
+            // Отставший клиент пропускает старые события и продолжает с новых
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return,
+        }
+    }
 }